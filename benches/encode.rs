@@ -13,6 +13,14 @@ fn encode_benchmark(c: &mut Criterion) {
         })
     });
 
+    c.bench_function("encode_group", |b| {
+        b.iter(|| {
+            for group in testdata.chunks_exact(4).take(1024) {
+                let _ = encode_group(black_box([group[0], group[1], group[2], group[3]]));
+            }
+        })
+    });
+
     c.bench_function("decoder", |b| {
         b.iter(|| {
             let _ = decode(black_box(&encoded));
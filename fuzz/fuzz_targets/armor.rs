@@ -0,0 +1,16 @@
+#![no_main]
+
+use base85rs::armor;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, &[u8], bool)| {
+    let (instr, data, checksum) = input;
+    let _ = armor::decode(instr);
+
+    let armored = if checksum {
+        armor::encode_with_checksum(data, &[("Comment", "fuzz")])
+    } else {
+        armor::encode(data, &[])
+    };
+    assert_eq!(armor::decode(&armored).unwrap().data, data);
+});
@@ -0,0 +1,28 @@
+#![no_main]
+
+use base85rs::ascii85::{self, DecodeOptions, EncodeOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&[u8], &[u8], EncodeOptions, DecodeOptions)| {
+    let (instr, data, encode_options, decode_options) = input;
+    let _ = ascii85::decode_with_options(instr, &decode_options);
+    let _ = ascii85::decode_pdf(instr);
+
+    assert_eq!(
+        ascii85::decode_pdf(ascii85::encode_pdf(data).as_bytes()).as_deref(),
+        Ok(data)
+    );
+    // Padding adds bytes, and the Adobe markers need the matching decode option
+    if !encode_options.pad {
+        let encoded = ascii85::encode_with_options(data, &encode_options);
+        let options = DecodeOptions {
+            fold_spaces: encode_options.fold_spaces,
+            adobe: encode_options.adobe,
+            ..DecodeOptions::default()
+        };
+        assert_eq!(
+            ascii85::decode_with_options(encoded.as_bytes(), &options).as_deref(),
+            Ok(data)
+        );
+    }
+});
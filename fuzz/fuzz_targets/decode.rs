@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Arbitrary text either fails to decode, or decodes to bytes encoding back to an equivalent text.
+fuzz_target!(|instr: &str| {
+    if let Some(decoded) = base85rs::decode(instr) {
+        assert_eq!(base85rs::decode(&base85rs::encode(&decoded)), Some(decoded));
+    }
+});
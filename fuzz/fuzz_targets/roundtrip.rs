@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let encoded = base85rs::encode(data);
+    assert_eq!(encoded.len(), base85rs::encoded_len(data.len()));
+    assert_eq!(base85rs::decode(&encoded).as_deref(), Some(data));
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The streaming decoder agrees with the one-shot decoder, whatever the input.
+fuzz_target!(|instr: &[u8]| {
+    let mut streamed = Vec::new();
+    let result = base85rs::transcode::base85_to_bytes(instr, &mut streamed);
+    match std::str::from_utf8(instr).ok().and_then(base85rs::decode) {
+        Some(decoded) => {
+            assert_eq!(result.ok(), Some(decoded.len() as u64));
+            assert_eq!(streamed, decoded);
+        }
+        // Bytes of invalid UTF-8 are outside of the alphabet too
+        None => assert!(result.is_err()),
+    }
+});
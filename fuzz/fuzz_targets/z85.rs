@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, &[u8])| {
+    let (instr, data) = input;
+    if let Ok(decoded) = base85rs::z85::decode(instr) {
+        assert_eq!(
+            base85rs::z85::decode(&base85rs::z85::encode(&decoded)),
+            Ok(decoded)
+        );
+    }
+    assert_eq!(
+        base85rs::z85::decode(&base85rs::z85::encode(data)).as_deref(),
+        Ok(data)
+    );
+    let _ = base85rs::z85::decode_key(instr);
+});
@@ -0,0 +1,63 @@
+#ifndef BASE85RS_H
+#define BASE85RS_H
+
+/* Generated by cbindgen from src/capi.rs, do not edit */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The operation succeeded
+#define BASE85_OK 0
+
+// A required pointer was null
+#define BASE85_ERROR_NULL_POINTER -1
+
+// The output buffer is too small
+#define BASE85_ERROR_OUTPUT_TOO_SMALL -2
+
+// The input contains a character outside of the alphabet
+#define BASE85_ERROR_INVALID_BYTE -3
+
+// A group of 5 characters of the input exceeds 32 bits
+#define BASE85_ERROR_OVERFLOW -4
+
+// The input is malformed in another way
+#define BASE85_ERROR_INVALID_INPUT -5
+
+// Returns the number of characters needed to encode `len` bytes
+size_t base85_encoded_len(size_t len);
+
+// Returns the maximum number of bytes produced by decoding `len` characters
+size_t base85_decoded_len(size_t len);
+
+// Encodes `data_len` bytes from `data` into `out`, which can hold `out_len` characters
+//
+// On success, the number of characters written is stored in `written`. The output isn't
+// NUL terminated.
+//
+// # Safety
+//
+// `data` must be valid for reads of `data_len` bytes, `out` valid for writes of `out_len`
+// bytes and `written` valid for a write. Pointers may only be null for empty buffers.
+int32_t base85_encode(const uint8_t *data,
+                      size_t data_len,
+                      uint8_t *out,
+                      size_t out_len,
+                      size_t *written);
+
+// Decodes `instr_len` characters from `instr` into `out`, which can hold `out_len` bytes
+//
+// On success, the number of bytes written is stored in `written`. When an invalid character
+// or an overflowing group is found, its index in the input is stored in `written` instead.
+//
+// # Safety
+//
+// `instr` must be valid for reads of `instr_len` bytes, `out` valid for writes of `out_len`
+// bytes and `written` valid for a write. Pointers may only be null for empty buffers.
+int32_t base85_decode(const uint8_t *instr,
+                      size_t instr_len,
+                      uint8_t *out,
+                      size_t out_len,
+                      size_t *written);
+
+#endif  /* BASE85RS_H */
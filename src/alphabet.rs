@@ -0,0 +1,238 @@
+//! Custom alphabets, and curated presets for contexts where some RFC1924 characters are painful
+//!
+//! Groups are built the same way as in the RFC1924 variant, only the characters change.
+//!
+//! # Example
+//!
+//! ```
+//! use base85rs::{decode_with_alphabet, encode_with_alphabet, Alphabet};
+//!
+//! let encoded = encode_with_alphabet(b"aaaaa", &Alphabet::SHELL_SAFE);
+//! assert_eq!(encoded, "VPRomVE");
+//! assert_eq!(decode_with_alphabet(&encoded, &Alphabet::SHELL_SAFE).unwrap(), b"aaaaa");
+//! ```
+
+use std::fmt;
+
+use crate::{
+    decode_vec, digits_table, to_x85, AlphabetError, DecodeError, INVALID_DIGIT, RFC1924_ALPHABET,
+};
+
+/// 85 distinct printable ASCII characters, in the order of their values
+///
+/// Both the encoding and the decoding tables are computed once, when the alphabet is built.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Alphabet {
+    chars: [u8; 85],
+    digits: [u8; 256],
+}
+
+impl Alphabet {
+    /// The RFC1924 alphabet, used by `encode()` and `decode()`
+    pub const RFC1924: Self = Self::from_chars(RFC1924_ALPHABET);
+
+    /// An alphabet without ``"$'\`!{}#``, safe inside single or double quotes of a shell
+    pub const SHELL_SAFE: Self = Self::from_chars(
+        b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz%&()*+,-./:;<=>?@[]^_|~",
+    );
+
+    /// An alphabet without ``"\/<>&'`$``, left unescaped in JSON strings by common encoders,
+    /// including the HTML and JavaScript safe ones
+    pub const JSON_SAFE: Self = Self::from_chars(
+        b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#%()*+,-.:;=?@[]^_{|}~",
+    );
+
+    /// The Z85 alphabet, used by `z85::encode()` and `z85::decode()`
+    #[cfg(feature = "z85")]
+    pub const Z85: Self = Self::from_chars(crate::z85::ALPHABET);
+
+    /// The order-preserving alphabet, used by `ordered::encode()` and `ordered::decode()`
+    #[cfg(feature = "ordered")]
+    pub const ORDERED: Self = Self::from_chars(crate::ordered::ALPHABET);
+
+    // Build the tables of already checked `chars`
+    const fn from_chars(chars: &[u8; 85]) -> Self {
+        Self {
+            chars: *chars,
+            digits: digits_table(chars),
+        }
+    }
+
+    /// `new()` checks that `chars` are 85 distinct printable ASCII characters
+    ///
+    /// # Errors
+    ///
+    /// Returns `AlphabetError::InvalidChar` for a space, a control or a non-ASCII character, and
+    /// `AlphabetError::DuplicateChar` for a character found twice.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut chars = *base85rs::RFC1924_ALPHABET;
+    /// chars.swap(0, 1);
+    /// assert!(base85rs::Alphabet::new(&chars).is_ok());
+    /// chars[0] = b'2';
+    /// assert!(base85rs::Alphabet::new(&chars).is_err());
+    /// ```
+    pub fn new(chars: &[u8; 85]) -> Result<Self, AlphabetError> {
+        let mut seen = [false; 128];
+        for &chr in chars {
+            if !chr.is_ascii_graphic() {
+                return Err(AlphabetError::InvalidChar(chr));
+            }
+            if seen[usize::from(chr)] {
+                return Err(AlphabetError::DuplicateChar(chr));
+            }
+            seen[usize::from(chr)] = true;
+        }
+        Ok(Self::from_chars(chars))
+    }
+
+    /// `chars()` returns the 85 characters, in the order of their values
+    #[must_use]
+    pub fn chars(&self) -> &[u8; 85] {
+        &self.chars
+    }
+}
+
+impl fmt::Debug for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Alphabet")
+            .field(&String::from_utf8_lossy(&self.chars))
+            .finish()
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self::RFC1924
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Alphabet {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.choose(&[Self::RFC1924, Self::SHELL_SAFE, Self::JSON_SAFE])
+            .copied()
+    }
+}
+
+/// `encode_with_alphabet()` turns a slice of bytes into a `String` encoded with `alphabet`
+///
+/// # Example
+///
+/// ```
+/// use base85rs::{encode_with_alphabet, Alphabet};
+///
+/// assert_eq!(encode_with_alphabet(b"\xff\xff\xff\xff", &Alphabet::JSON_SAFE), "|NsC0");
+/// assert_eq!(encode_with_alphabet(b"\xff", &Alphabet::SHELL_SAFE), "^^");
+/// ```
+#[must_use]
+pub fn encode_with_alphabet(data: &[u8], alphabet: &Alphabet) -> String {
+    crate::encode(data)
+        .bytes()
+        .filter_map(to_x85)
+        .map(|digit| char::from(alphabet.chars[usize::from(digit)]))
+        .collect()
+}
+
+/// `decode_with_alphabet()` try to decode a &str encoded with `alphabet`, skipping whitespaces
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of `alphabet`
+/// and `DecodeError::GroupOverflow` if a group exceeds `u32::MAX`.
+pub fn decode_with_alphabet(instr: &str, alphabet: &Alphabet) -> Result<Vec<u8>, DecodeError> {
+    let translated = instr
+        .bytes()
+        .enumerate()
+        .map(
+            |(index, byte)| match (byte, alphabet.digits[usize::from(byte)]) {
+                (0x20, _) => Ok(byte),
+                (_, INVALID_DIGIT) => Err(DecodeError::InvalidByte { index, byte }),
+                (_, digit) => Ok(RFC1924_ALPHABET[usize::from(digit)]),
+            },
+        )
+        .collect::<Result<Vec<u8>, DecodeError>>()?;
+    decode_vec(&translated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_valid() {
+        for alphabet in [
+            Alphabet::RFC1924,
+            Alphabet::SHELL_SAFE,
+            Alphabet::JSON_SAFE,
+            #[cfg(feature = "z85")]
+            Alphabet::Z85,
+            #[cfg(feature = "ordered")]
+            Alphabet::ORDERED,
+        ] {
+            assert_eq!(Alphabet::new(alphabet.chars()), Ok(alphabet));
+        }
+        assert!(!Alphabet::SHELL_SAFE
+            .chars()
+            .iter()
+            .any(|chr| b"\"$'\\`!{}#".contains(chr)));
+        assert!(!Alphabet::JSON_SAFE
+            .chars()
+            .iter()
+            .any(|chr| b"\"\\/<>&'`$".contains(chr)));
+    }
+
+    #[test]
+    fn alphabet_invalid() {
+        let mut chars = *RFC1924_ALPHABET;
+        chars[84] = b' ';
+        assert_eq!(Alphabet::new(&chars), Err(AlphabetError::InvalidChar(b' ')));
+        chars[84] = 0xC3;
+        assert_eq!(Alphabet::new(&chars), Err(AlphabetError::InvalidChar(0xC3)));
+        chars[84] = b'0';
+        assert_eq!(
+            Alphabet::new(&chars),
+            Err(AlphabetError::DuplicateChar(b'0'))
+        );
+    }
+
+    #[test]
+    fn alphabet_roundtrip() {
+        for alphabet in [Alphabet::RFC1924, Alphabet::SHELL_SAFE, Alphabet::JSON_SAFE] {
+            for len in 0..16 {
+                let data: Vec<u8> = (0..len).map(|i| i * 17).collect();
+                let encoded = encode_with_alphabet(&data, &alphabet);
+                assert_eq!(decode_with_alphabet(&encoded, &alphabet).unwrap(), data);
+            }
+        }
+        assert_eq!(
+            encode_with_alphabet(b"aaaaa", &Alphabet::RFC1924),
+            crate::encode(b"aaaaa")
+        );
+    }
+
+    #[test]
+    fn alphabet_debug() {
+        assert_eq!(
+            format!("{:?}", Alphabet::RFC1924),
+            format!(
+                "Alphabet({:?})",
+                std::str::from_utf8(RFC1924_ALPHABET).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn decode_with_alphabet_invalid() {
+        // `$` is part of RFC1924, not of the shell-safe alphabet
+        assert_eq!(
+            decode_with_alphabet("VP$om", &Alphabet::SHELL_SAFE),
+            Err(DecodeError::InvalidByte {
+                index: 2,
+                byte: b'$'
+            })
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! PEM-style armored format, to embed base85 blobs in config files or emails
+//!
+//! ```text
+//! -----BEGIN BASE85-----
+//! Comment: optional headers
+//!
+//! VPRomVE
+//! -----END BASE85-----
+//! ```
+//!
+//! Encoded data is wrapped every 64 characters. Since `:` isn't part of the alphabet, header
+//! lines are told apart from data lines without ambiguity. An optional `=XXXX` line before the
+//! `END` line holds the CRC-24 of the data, encoded in base85, as `OpenPGP` armor does.
+//!
+//! # Example
+//!
+//! ```
+//! let armored = base85rs::armor::encode(b"aaaaa", &[("Comment", "five a")]);
+//! let decoded = base85rs::armor::decode(&armored).unwrap();
+//! assert_eq!(decoded.data, b"aaaaa");
+//! assert_eq!(decoded.headers, [("Comment".to_string(), "five a".to_string())]);
+//! ```
+
+use crate::checksum::crc24;
+use crate::{incremental, DecodeError};
+
+/// First line of an armored block
+pub const BEGIN: &str = "-----BEGIN BASE85-----";
+/// Last line of an armored block
+pub const END: &str = "-----END BASE85-----";
+/// Number of encoded characters per line
+pub const LINE_WIDTH: usize = 64;
+
+/// The content of an armored block
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Armored {
+    /// Headers, as `(name, value)` pairs in their order of appearance
+    pub headers: Vec<(String, String)>,
+    /// Decoded data
+    pub data: Vec<u8>,
+}
+
+/// `encode()` turns a slice of bytes into an armored block with the given headers
+///
+/// Header names and values must not contain line breaks, and names must not contain `:`.
+///
+/// # Example
+///
+/// ```
+/// let armored = base85rs::armor::encode(b"aaaaa", &[]);
+/// assert_eq!(armored, "-----BEGIN BASE85-----\nVPRomVE\n-----END BASE85-----\n");
+/// ```
+#[must_use]
+pub fn encode(data: &[u8], headers: &[(&str, &str)]) -> String {
+    armor(data, headers, false)
+}
+
+/// `encode_with_checksum()` works like `encode()`, adding an OpenPGP-style `=XXXX` line
+/// holding the CRC-24 of the data before the `END` line
+///
+/// # Example
+///
+/// ```
+/// let armored = base85rs::armor::encode_with_checksum(b"aaaaa", &[]);
+/// assert_eq!(
+///     armored,
+///     "-----BEGIN BASE85-----\nVPRomVE\n=<Q44\n-----END BASE85-----\n"
+/// );
+/// ```
+#[must_use]
+pub fn encode_with_checksum(data: &[u8], headers: &[(&str, &str)]) -> String {
+    armor(data, headers, true)
+}
+
+fn armor(data: &[u8], headers: &[(&str, &str)], checksum: bool) -> String {
+    let encoded = crate::encode(data);
+    let mut outdata = String::with_capacity(encoded.len() + encoded.len() / LINE_WIDTH + 64);
+
+    outdata.push_str(BEGIN);
+    outdata.push('\n');
+    for (name, value) in headers {
+        outdata.push_str(name);
+        outdata.push_str(": ");
+        outdata.push_str(value);
+        outdata.push('\n');
+    }
+    if !headers.is_empty() {
+        outdata.push('\n');
+    }
+
+    // A last line that looks like a checksum line is made one character longer, taken from the
+    // line before, or split in two when alone, so that decoding isn't ambiguous
+    let mut split = encoded.len() - encoded.len() % LINE_WIDTH;
+    if is_checksum_line(&encoded[split..]) {
+        split = split.checked_sub(1).unwrap_or(3);
+    }
+    // The encoded data is ASCII only, it can be split anywhere
+    let (body, last) = encoded.split_at(split);
+    for line in body.as_bytes().chunks(LINE_WIDTH).chain([last.as_bytes()]) {
+        if !line.is_empty() {
+            outdata.extend(line.iter().map(|&chr| char::from(chr)));
+            outdata.push('\n');
+        }
+    }
+
+    if checksum {
+        let crc = crc24(data).to_be_bytes();
+        outdata.push('=');
+        outdata.push_str(&crate::encode(&crc[1..]));
+        outdata.push('\n');
+    }
+    outdata.push_str(END);
+    outdata.push('\n');
+    outdata
+}
+
+fn is_checksum_line(line: &str) -> bool {
+    line.len() == 5 && line.starts_with('=')
+}
+
+/// `decode()` try to decode the first armored block found in `instr`
+///
+/// The parser is tolerant: anything before the `BEGIN` line or after the `END` line is ignored,
+/// as are blank lines, surrounding whitespaces and `\r\n` line endings. A checksum line, either
+/// written by `encode_with_checksum()` or by another tool, is verified when present.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidArmor` if the `BEGIN` or `END` line is missing,
+/// `DecodeError::InvalidByte` if the data contains a character outside of the alphabet and
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX`, their index being relative to the
+/// whole input, and `DecodeError::ChecksumMismatch` if the checksum line doesn't match the data.
+pub fn decode(instr: &str) -> Result<Armored, DecodeError> {
+    let mut lines = instr.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    });
+
+    lines
+        .find(|(_, line)| line.trim() == BEGIN)
+        .ok_or(DecodeError::InvalidArmor)?;
+
+    let mut armored = Armored::default();
+    let mut decoder = incremental::Decoder::default();
+    let mut in_headers = true;
+    // A line that looks like a checksum line is only known to be one when followed by `END`
+    let mut pending: Option<(usize, &str)> = None;
+
+    for (start, line) in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed == END {
+            decoder.finish(|bytes| {
+                armored.data.extend(bytes);
+                Ok(())
+            })?;
+            if let Some((_, checksum)) = pending {
+                let crc = crc24(&armored.data).to_be_bytes();
+                if checksum.trim()[1..] != crate::encode(&crc[1..]) {
+                    return Err(DecodeError::ChecksumMismatch);
+                }
+            }
+            return Ok(armored);
+        }
+        match trimmed.split_once(':') {
+            Some((name, value)) if in_headers => armored
+                .headers
+                .push((name.trim().to_string(), value.trim().to_string())),
+            _ => {
+                in_headers = false;
+                if let Some((start, line)) = pending.take() {
+                    decode_line(&mut decoder, start, line, &mut armored.data)?;
+                }
+                if is_checksum_line(trimmed) {
+                    pending = Some((start, line));
+                } else {
+                    decode_line(&mut decoder, start, line, &mut armored.data)?;
+                }
+            }
+        }
+    }
+    Err(DecodeError::InvalidArmor)
+}
+
+fn decode_line(
+    decoder: &mut incremental::Decoder,
+    start: usize,
+    line: &str,
+    data: &mut Vec<u8>,
+) -> Result<(), DecodeError> {
+    let leading = line.len() - line.trim_start().len();
+    decoder.set_index(start + leading);
+    decoder.update(line.trim().as_bytes(), |bytes| {
+        data.extend(bytes);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn armor_roundtrip() {
+        let data: Vec<u8> = (0..=255).collect();
+        let armored = encode(&data, &[("Version", "1"), ("Comment", "all bytes")]);
+        assert!(armored.lines().all(|line| line.len() <= LINE_WIDTH));
+
+        let decoded = decode(&armored).unwrap();
+        assert_eq!(decoded.data, data);
+        assert_eq!(
+            decoded.headers,
+            [
+                ("Version".to_string(), "1".to_string()),
+                ("Comment".to_string(), "all bytes".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn armor_empty() {
+        let armored = encode(b"", &[]);
+        assert_eq!(armored, format!("{BEGIN}\n{END}\n"));
+        assert_eq!(decode(&armored).unwrap(), Armored::default());
+    }
+
+    #[test]
+    fn decode_tolerant() {
+        let armored = "Some mail text\r\n  -----BEGIN BASE85-----  \r\nComment:x\r\n\r\n VPRo\r\n\r\nmVE \r\n-----END BASE85-----\r\nSignature";
+        let decoded = decode(armored).unwrap();
+        assert_eq!(decoded.data, b"aaaaa");
+        assert_eq!(decoded.headers, [("Comment".to_string(), "x".to_string())]);
+    }
+
+    #[test]
+    fn armor_checksum() {
+        for len in 0..200 {
+            let data: Vec<u8> = (0..len).collect();
+            let armored = encode_with_checksum(&data, &[]);
+            assert_eq!(decode(&armored).unwrap().data, data);
+            assert_eq!(decode(&encode(&data, &[])).unwrap().data, data);
+        }
+    }
+
+    #[test]
+    fn armor_checksum_like_data() {
+        // "=0000" as the only line, and as the last line
+        let data = 0xE63E_5B2A_u32.to_be_bytes();
+        assert_eq!(crate::encode(&data), "=0000");
+        assert_eq!(
+            encode(&data, &[]),
+            "-----BEGIN BASE85-----\n=00\n00\n-----END BASE85-----\n"
+        );
+        assert_eq!(decode(&encode(&data, &[])).unwrap().data, data);
+
+        let data = [[0; 256].as_slice(), &data].concat();
+        let armored = encode(&data, &[]);
+        assert!(armored.lines().any(|line| line == "0=0000"));
+        assert_eq!(decode(&armored).unwrap().data, data);
+    }
+
+    #[test]
+    fn armor_checksum_mismatch() {
+        let armored = encode_with_checksum(b"aaaaa", &[]).replace("VPRomVE", "VPRonVE");
+        assert_eq!(decode(&armored), Err(DecodeError::ChecksumMismatch));
+        let armored = encode_with_checksum(b"aaaaa", &[]).replace("VPRomVE", "VPR");
+        assert_eq!(decode(&armored), Err(DecodeError::ChecksumMismatch));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(decode("VPRomVE"), Err(DecodeError::InvalidArmor));
+        assert_eq!(
+            decode("-----BEGIN BASE85-----\nVPRomVE\n"),
+            Err(DecodeError::InvalidArmor)
+        );
+        assert_eq!(
+            decode("-----BEGIN BASE85-----\nVP\n  R]\n-----END BASE85-----\n"),
+            Err(DecodeError::InvalidByte {
+                index: 29,
+                byte: b']'
+            })
+        );
+    }
+}
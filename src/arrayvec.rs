@@ -0,0 +1,42 @@
+//! Encoding into a fixed capacity `ArrayString`, entirely on the stack
+
+use crate::{ascii_str, encode_slice, EncodeError};
+
+/// `encode_array_string()` turns a slice of bytes into a base85 encoded `arrayvec::ArrayString`
+///
+/// # Errors
+///
+/// Returns `EncodeError::OutputTooSmall` if the encoded data doesn't fit in `CAP` characters.
+///
+/// # Example
+///
+/// ```
+/// let encoded = base85rs::encode_array_string::<8>(b"aaaaa").unwrap();
+/// assert_eq!(encoded.as_str(), "VPRomVE");
+/// ```
+pub fn encode_array_string<const CAP: usize>(
+    data: &[u8],
+) -> Result<::arrayvec::ArrayString<CAP>, EncodeError> {
+    let mut buffer = [0; CAP];
+    let len = encode_slice(data, &mut buffer)?;
+    ::arrayvec::ArrayString::from(ascii_str(&buffer[..len]))
+        .map_err(|_| EncodeError::OutputTooSmall)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_capacity() {
+        assert_eq!(
+            encode_array_string::<7>(b"aaaaa").unwrap().as_str(),
+            "VPRomVE"
+        );
+        assert_eq!(encode_array_string::<0>(b"").unwrap().as_str(), "");
+        assert_eq!(
+            encode_array_string::<6>(b"aaaaa"),
+            Err(EncodeError::OutputTooSmall)
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! Encoding and decoding of the ASCII85 variant, used by `btoa`, PostScript and PDF
+//!
+//! Characters go from `!` to `u`, and a group of four zero bytes is written `z`. `encode()` and
+//! `decode()` behave like `base64.a85encode()` and `base64.a85decode()` of Python, while
+//! `encode_pdf()` and `decode_pdf()` follow the `ASCII85Decode` filter of the PDF specification.
+//!
+//! # Example
+//!
+//! ```
+//! assert_eq!(base85rs::ascii85::encode(b"Hello World!"), "87cURD]i,\"Ebo80");
+//! ```
+
+use crate::{decode_digits, decoded_len, encoded_len, DecodeError};
+
+/// The 85 characters of the ASCII85 alphabet, in the order of their values, from `!` to `u`
+pub const ALPHABET: &[u8; 85] = &{
+    let mut alphabet = [0; 85];
+    let mut i = 0;
+    while i < 85 {
+        #[allow(clippy::cast_possible_truncation)]
+        let chr = b'!' + i as u8;
+        alphabet[i] = chr;
+        i += 1;
+    }
+    alphabet
+};
+
+// Characters ignored by `decode()`, as in Python
+const DEFAULT_IGNORE_CHARS: &[u8] = b" \t\n\r\x0b";
+// White-space characters of the PDF specification
+const PDF_WHITESPACES: &[u8] = b"\0\t\n\x0c\r ";
+
+/// Options of `encode_with_options()`, named after the arguments of `base64.a85encode()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct EncodeOptions {
+    /// Write a group of four spaces `y`, as `btoa` does
+    pub fold_spaces: bool,
+    /// Wrap the output every `wrap_col` characters, 0 to disable
+    pub wrap_col: usize,
+    /// Complete the trailing partial group with zero bytes
+    pub pad: bool,
+    /// Surround the output with the `<~` and `~>` Adobe markers
+    pub adobe: bool,
+}
+
+/// Options of `decode_with_options()`, named after the arguments of `base64.a85decode()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DecodeOptions<'a> {
+    /// Accept `y` for a group of four spaces
+    pub fold_spaces: bool,
+    /// Expect the input to end with the `~>` Adobe marker, and maybe start with `<~`
+    pub adobe: bool,
+    /// Characters skipped in the input, spaces, tabs and line breaks by default
+    pub ignore_chars: &'a [u8],
+}
+
+impl Default for DecodeOptions<'_> {
+    fn default() -> Self {
+        Self {
+            fold_spaces: false,
+            adobe: false,
+            ignore_chars: DEFAULT_IGNORE_CHARS,
+        }
+    }
+}
+
+fn encode_groups(data: &[u8], fold_spaces: bool, pad: bool, outdata: &mut String) {
+    for chunk in data.chunks(4) {
+        let mut group = [0; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let len = if pad { 4 } else { chunk.len() };
+        if len == 4 && group == [0; 4] {
+            outdata.push('z');
+        } else if fold_spaces && group == [b' '; 4] {
+            outdata.push('y');
+        } else {
+            let mut value = u32::from_be_bytes(group);
+            let mut chars = [0; 5];
+            for chr in chars.iter_mut().rev() {
+                *chr = ALPHABET[usize::from((value % 85).to_le_bytes()[0])];
+                value /= 85;
+            }
+            outdata.extend(chars[..=len].iter().map(|&chr| char::from(chr)));
+        }
+    }
+}
+
+// Decoding rules, either Python's ones or the PDF specification's ones.
+struct Rules<'a> {
+    fold_spaces: bool,
+    ignore_chars: &'a [u8],
+    pdf: bool,
+}
+
+fn decode_with(instr: &[u8], rules: &Rules<'_>) -> Result<Vec<u8>, DecodeError> {
+    let mut outdata = Vec::with_capacity(decoded_len(instr.len()));
+    let mut digits = [0; 5];
+    let mut filled = 0;
+    // Index of the first digit of the current group
+    let mut start = 0;
+    let mut terminated = false;
+
+    let mut bytes = instr.iter().copied().enumerate();
+    while let Some((index, byte)) = bytes.next() {
+        match byte {
+            b'!'..=b'u' => {
+                if filled == 0 {
+                    start = index;
+                }
+                digits[filled] = byte - b'!';
+                filled += 1;
+                if filled == 5 {
+                    outdata.extend(decode_digits(digits, start)?);
+                    filled = 0;
+                }
+            }
+            b'z' if filled == 0 => outdata.extend([0; 4]),
+            b'y' if filled == 0 && rules.fold_spaces => outdata.extend([b' '; 4]),
+            b'~' if rules.pdf => {
+                if bytes.next().map(|(_, byte)| byte) != Some(b'>') {
+                    return Err(DecodeError::InvalidByte { index, byte });
+                }
+                terminated = true;
+                break;
+            }
+            _ if rules.ignore_chars.contains(&byte) => {}
+            _ => return Err(DecodeError::InvalidByte { index, byte }),
+        }
+    }
+
+    if rules.pdf && !terminated {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    match filled {
+        0 => {}
+        1 if rules.pdf => return Err(DecodeError::InvalidLength),
+        // A single trailing character doesn't carry a whole byte, but Python still rejects it
+        // when the completed group overflows
+        _ => {
+            digits[filled..].fill(84);
+            outdata.extend(&decode_digits(digits, start)?[..filled - 1]);
+        }
+    }
+    Ok(outdata)
+}
+
+/// `encode()` turns a slice of bytes into an ASCII85 encoded `String`
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::ascii85::encode(b"\0\0\0\0abc"), "z@:E^");
+/// ```
+#[must_use]
+pub fn encode(data: &[u8]) -> String {
+    encode_with_options(data, &EncodeOptions::default())
+}
+
+/// `decode()` try to decode an ASCII85 encoded &str, ignoring spaces, tabs and line breaks
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet
+/// or a misplaced `z`, and `DecodeError::GroupOverflow` if a group doesn't fit in 32 bits.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::ascii85::decode("z@:E^").unwrap(), b"\0\0\0\0abc");
+/// ```
+pub fn decode(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_with_options(instr.as_bytes(), &DecodeOptions::default())
+}
+
+/// `encode_with_options()` turns a slice of bytes into an ASCII85 encoded `String`, producing
+/// the same output as `base64.a85encode()` of Python with the same options
+///
+/// # Example
+///
+/// ```
+/// use base85rs::ascii85::{encode_with_options, EncodeOptions};
+///
+/// let options = EncodeOptions {
+///     adobe: true,
+///     wrap_col: 5,
+///     ..EncodeOptions::default()
+/// };
+/// assert_eq!(encode_with_options(b"abcdefgh", &options), "<~@:E\n_WAS,\nRg~>");
+/// ```
+#[must_use]
+pub fn encode_with_options(data: &[u8], options: &EncodeOptions) -> String {
+    let mut encoded = String::with_capacity(encoded_len(data.len()) + 4);
+    if options.adobe {
+        encoded.push_str("<~");
+    }
+    encode_groups(data, options.fold_spaces, options.pad, &mut encoded);
+
+    let mut outdata = if options.wrap_col == 0 {
+        encoded
+    } else {
+        let wrap_col = options.wrap_col.max(if options.adobe { 2 } else { 1 });
+        let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / wrap_col + 3);
+        for (index, chr) in encoded.chars().enumerate() {
+            if index > 0 && index % wrap_col == 0 {
+                wrapped.push('\n');
+            }
+            wrapped.push(chr);
+        }
+        // The end marker isn't split, it goes on a line of its own when needed
+        if options.adobe && (encoded.len() - 1) % wrap_col + 1 + 2 > wrap_col {
+            wrapped.push('\n');
+        }
+        wrapped
+    };
+    if options.adobe {
+        outdata.push_str("~>");
+    }
+    outdata
+}
+
+/// `decode_with_options()` try to decode ASCII85 encoded data, accepting the same inputs as
+/// `base64.a85decode()` of Python with the same options
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet
+/// and not ignored or a misplaced `z` or `y`, `DecodeError::UnexpectedEnd` if the Adobe end
+/// marker is expected but missing and `DecodeError::GroupOverflow` if a group doesn't fit in 32
+/// bits.
+///
+/// # Example
+///
+/// ```
+/// use base85rs::ascii85::{decode_with_options, DecodeOptions};
+///
+/// let options = DecodeOptions {
+///     fold_spaces: true,
+///     ..DecodeOptions::default()
+/// };
+/// assert_eq!(decode_with_options(b"y@:E^", &options).unwrap(), b"    abc");
+/// ```
+pub fn decode_with_options(
+    instr: &[u8],
+    options: &DecodeOptions<'_>,
+) -> Result<Vec<u8>, DecodeError> {
+    let rules = Rules {
+        fold_spaces: options.fold_spaces,
+        ignore_chars: options.ignore_chars,
+        pdf: false,
+    };
+    if !options.adobe {
+        return decode_with(instr, &rules);
+    }
+    let instr = instr
+        .strip_suffix(b"~>")
+        .ok_or(DecodeError::UnexpectedEnd)?;
+    match instr.strip_prefix(b"<~") {
+        Some(instr) => decode_with(instr, &rules).map_err(|err| err.offset_by(2)),
+        None => decode_with(instr, &rules),
+    }
+}
+
+/// `encode_pdf()` turns a slice of bytes into the content of an `ASCII85Decode` PDF stream,
+/// terminated by the `~>` end-of-data marker
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::ascii85::encode_pdf(b"Hello World!"), "87cURD]i,\"Ebo80~>");
+/// ```
+#[must_use]
+pub fn encode_pdf(data: &[u8]) -> String {
+    let mut outdata = encode(data);
+    outdata.push_str("~>");
+    outdata
+}
+
+/// `decode_pdf()` try to decode the content of an `ASCII85Decode` PDF stream
+///
+/// White-space characters are ignored and decoding stops at the `~>` end-of-data marker, as
+/// specified by ISO 32000-1, section 7.4.3.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// a misplaced `z` or a `~` not followed by `>`, `DecodeError::UnexpectedEnd` if the end-of-data
+/// marker is missing, `DecodeError::InvalidLength` if the final group is a single character and
+/// `DecodeError::GroupOverflow` if a group doesn't fit in 32 bits.
+///
+/// # Example
+///
+/// ```
+/// let decoded = base85rs::ascii85::decode_pdf(b"87cURD]i,\"Ebo80~>").unwrap();
+/// assert_eq!(decoded, b"Hello World!");
+/// ```
+pub fn decode_pdf(instr: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let rules = Rules {
+        fold_spaces: false,
+        ignore_chars: PDF_WHITESPACES,
+        pdf: true,
+    };
+    decode_with(instr, &rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii85_alphabet() {
+        assert_eq!(ALPHABET.first(), Some(&b'!'));
+        assert_eq!(ALPHABET.last(), Some(&b'u'));
+    }
+
+    #[test]
+    fn ascii85_python_vectors() {
+        let vectors: [(&[u8], &str); 5] = [
+            (b"Hello, World!", "87cURD_*#4DfTZ)+T"),
+            (b"\0\0\0\0abc", "z@:E^"),
+            (b"Man is distinguished", "9jqo^BlbD-BleB1DJ+*+F(f,q"),
+            (b"\xff\xff\xff\xff", "s8W-!"),
+            (b"\0", "!!"),
+        ];
+        for (data, encoded) in vectors {
+            assert_eq!(encode(data), encoded);
+            assert_eq!(decode(encoded).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn ascii85_roundtrip() {
+        for len in 0..16 {
+            let data: Vec<u8> = (0..len).map(|i| i * 17).collect();
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+            assert_eq!(decode_pdf(encode_pdf(&data).as_bytes()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn ascii85_invalid() {
+        assert_eq!(
+            decode("s8W-\""),
+            Err(DecodeError::GroupOverflow { index: 0 })
+        );
+        assert_eq!(
+            decode("!z!!!"),
+            Err(DecodeError::InvalidByte {
+                index: 1,
+                byte: b'z'
+            })
+        );
+        assert_eq!(
+            decode("87cUR~>"),
+            Err(DecodeError::InvalidByte {
+                index: 5,
+                byte: b'~'
+            })
+        );
+    }
+
+    #[test]
+    fn ascii85_python_options() {
+        let data = b"    hello world \0\0\0\0 and more text to wrap";
+        let fold_spaces = EncodeOptions {
+            fold_spaces: true,
+            ..EncodeOptions::default()
+        };
+        let encoded = "yBOu!rD]j7BEbo8/z+CT.u+Dl%;AKZ)+G]Y'MD]j7E@;l";
+        assert_eq!(encode_with_options(data, &fold_spaces), encoded);
+        let options = DecodeOptions {
+            fold_spaces: true,
+            ..DecodeOptions::default()
+        };
+        assert_eq!(
+            decode_with_options(encoded.as_bytes(), &options).unwrap(),
+            data
+        );
+
+        let adobe = EncodeOptions {
+            wrap_col: 10,
+            adobe: true,
+            ..EncodeOptions::default()
+        };
+        let encoded = "<~+<VdLBOu\n!rD]j7BEbo\n8/z+CT.u+D\nl%;AKZ)+G]\nY'MD]j7E@;\nl~>";
+        assert_eq!(encode_with_options(data, &adobe), encoded);
+        let options = DecodeOptions {
+            adobe: true,
+            ..DecodeOptions::default()
+        };
+        assert_eq!(
+            decode_with_options(encoded.as_bytes(), &options).unwrap(),
+            data
+        );
+
+        let empty = EncodeOptions {
+            wrap_col: 1,
+            adobe: true,
+            ..EncodeOptions::default()
+        };
+        assert_eq!(encode_with_options(b"", &empty), "<~\n~>");
+
+        let pad = EncodeOptions {
+            pad: true,
+            ..EncodeOptions::default()
+        };
+        assert_eq!(encode_with_options(b"abc", &pad), "@:E^H");
+        assert_eq!(encode_with_options(b"\0", &pad), "z");
+    }
+
+    #[test]
+    fn ascii85_python_decode_options() {
+        let adobe = DecodeOptions {
+            adobe: true,
+            ..DecodeOptions::default()
+        };
+        assert_eq!(decode_with_options(b"<~@:E^~>", &adobe).unwrap(), b"abc");
+        assert_eq!(decode_with_options(b"@:E^~>", &adobe).unwrap(), b"abc");
+        assert_eq!(
+            decode_with_options(b"<~@:E^", &adobe),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            decode_with_options(b"<~@:{^~>", &adobe),
+            Err(DecodeError::InvalidByte {
+                index: 4,
+                byte: b'{'
+            })
+        );
+
+        let ignore = DecodeOptions {
+            ignore_chars: b"X",
+            ..DecodeOptions::default()
+        };
+        assert_eq!(decode_with_options(b"@:E_X", &ignore).unwrap(), b"abce");
+        assert!(decode_with_options(b"@:E_ ", &ignore).is_err());
+
+        assert_eq!(decode("!").unwrap(), b"");
+        assert_eq!(decode("s"), Err(DecodeError::GroupOverflow { index: 0 }));
+        assert_eq!(decode("zs"), Err(DecodeError::GroupOverflow { index: 1 }));
+        assert!(decode("y").is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_options() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let raw: Vec<u8> = (0..=255).collect();
+        let mut unstructured = Unstructured::new(&raw);
+        let encode_options = EncodeOptions::arbitrary(&mut unstructured).unwrap();
+        let decode_options = DecodeOptions::arbitrary(&mut unstructured).unwrap();
+        let encoded = encode_with_options(b"arbitrary", &encode_options);
+        // Whatever the options, decoding must not panic
+        let _ = decode_with_options(encoded.as_bytes(), &decode_options);
+    }
+
+    // Stream of a PDF file, as wrapped by the writer
+    #[test]
+    fn pdf_stream() {
+        let stream = b"9jqo^BlbD-BleB1DJ+*+F(f,q/0JhKF<GL>Cj@.4Gp$d7F!,L7@<6@)/0JDEF<G%<+EV:2F!,\r\n\
+                       O<DJ+*.@<*K0@<6L(Df-\\0Ec5e;DffZ(EZee.Bl.9pF\"AGXBPCsi+DGm>@3BB/F*&OCAfu2/AKY\r\n\
+                       i(DIb:@FD,*)+C]U=@3BN#EcYf8ATD3s@q?d$AftVqCh[NqF<G:8+EV:.+Cf>-FD5W8ARlolDIa\r\n\
+                       l(DId<j@<?3r@:F%a+D58'ATD4$Bl@l3De:,-DJs`8ARoFb/0JMK@qB4^F!,R<AKZ&-DfTqBG%G\r\n\
+                       >uD.RTpAKYo'+CT/5+Cei#DII?(E,9)oF*2M7/c~>\r\nendstream";
+        let decoded = decode_pdf(stream).unwrap();
+        assert!(decoded.starts_with(b"Man is distinguished, not only by his reason"));
+        assert!(decoded.ends_with(b"carnal pleasure."));
+    }
+
+    #[test]
+    fn pdf_whitespaces_and_zeros() {
+        assert_eq!(
+            decode_pdf(b"z\0\x0c\t z 87cURD]i,\"Ebo80~>").unwrap(),
+            [[0; 8].as_slice(), b"Hello World!"].concat()
+        );
+    }
+
+    #[test]
+    fn pdf_invalid() {
+        assert_eq!(decode_pdf(b"87cURD"), Err(DecodeError::UnexpectedEnd));
+        assert_eq!(decode_pdf(b"87cUR8~>"), Err(DecodeError::InvalidLength));
+        assert_eq!(decode("87cUR8").unwrap(), b"Hell");
+        assert_eq!(
+            decode_pdf(b"87cUR~x"),
+            Err(DecodeError::InvalidByte {
+                index: 5,
+                byte: b'~'
+            })
+        );
+        assert_eq!(
+            decode_pdf(b"\x0b~>"),
+            Err(DecodeError::InvalidByte {
+                index: 0,
+                byte: 0x0b
+            })
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Whole-message radix mode, where the entire input is a single big number written in base 85
+//!
+//! This is **not** compatible with the chunked mode of `encode()` / `decode()`, which works on
+//! groups of 4 bytes. As with base58, every leading zero byte is rendered as a leading `0`
+//! character so that leading zeros survive a roundtrip.
+//!
+//! # Example
+//!
+//! ```
+//! let encoded = base85rs::bignum::encode(b"\0aaaaa");
+//! assert_eq!(encoded, "019MN9Py");
+//! assert_eq!(base85rs::bignum::decode(&encoded).unwrap(), b"\0aaaaa");
+//! ```
+
+use num_bigint::BigUint;
+
+use crate::{to_x85, DecodeError, RFC1924_ALPHABET};
+
+/// `encode()` turns a slice of bytes into the base85 representation of the number it contains
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::bignum::encode(b"a"), "1C");
+/// ```
+#[must_use]
+pub fn encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&byte| byte == 0).count();
+    let mut outdata = "0".repeat(zeros);
+
+    if zeros < data.len() {
+        outdata.extend(
+            BigUint::from_bytes_be(&data[zeros..])
+                .to_radix_be(85)
+                .into_iter()
+                .map(|digit| char::from(RFC1924_ALPHABET[usize::from(digit)])),
+        );
+    }
+    outdata
+}
+
+/// `decode()` try to decode the base85 representation of a number into a `Vec<u8>`
+///
+/// As with the chunked mode, whitespaces are ignored.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::bignum::decode("1C").unwrap(), b"a");
+/// ```
+pub fn decode(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    let digits = instr
+        .bytes()
+        .enumerate()
+        .filter(|&(_, chr)| chr != 0x20)
+        .map(|(index, chr)| to_x85(chr).ok_or(DecodeError::InvalidByte { index, byte: chr }))
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    let zeros = digits.iter().take_while(|&&digit| digit == 0).count();
+    let mut outdata = vec![0; zeros];
+
+    if zeros < digits.len() {
+        // Digits have been checked above, they can't be out of range
+        let value = BigUint::from_radix_be(&digits[zeros..], 85).unwrap_or_default();
+        outdata.extend(value.to_bytes_be());
+    }
+    Ok(outdata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_empty() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"\0\0"), "00");
+    }
+
+    #[test]
+    fn decode_empty() {
+        assert_eq!(decode("").unwrap(), b"");
+        assert_eq!(decode("00").unwrap(), b"\0\0");
+    }
+
+    #[test]
+    fn bignum_roundtrip() {
+        let wordlist = [
+            "relimitation".as_bytes(),
+            b"\0\0pollenless",
+            b"\0",
+            b"\xff\xff\xff\xff\xff",
+        ];
+        for word in wordlist {
+            assert_eq!(decode(&encode(word)).unwrap(), word);
+        }
+    }
+
+    #[test]
+    fn decode_with_whitespace() {
+        assert_eq!(decode("019M N9Py").unwrap(), b"\0aaaaa");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(
+            decode("1C]"),
+            Err(DecodeError::InvalidByte {
+                index: 2,
+                byte: b']'
+            })
+        );
+    }
+}
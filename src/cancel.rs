@@ -0,0 +1,121 @@
+//! Cooperative cancellation of long-running conversions
+
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::error::Cancelled;
+
+/// `CancellationToken` aborts the conversions it is given to, from any thread
+///
+/// Clones share the same state: cancelling one cancels them all. Conversions check the token
+/// between chunks, and fail with an error wrapping `Cancelled` once it is cancelled, leaving what
+/// was already written to their output.
+///
+/// # Example
+///
+/// ```
+/// let token = base85rs::CancellationToken::new();
+/// let mut reader = token.reader("aaaaa".as_bytes());
+/// token.cancel();
+/// let err = base85rs::copy_encode(&mut reader, &mut std::io::sink()).unwrap_err();
+/// assert!(base85rs::CancellationToken::is_cancellation(&err));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// `new()` creates a token, not cancelled yet
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `cancel()` asks the conversions using this token to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// `is_cancelled()` tells whether `cancel()` has been called
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// `reader()` wraps `inner`, failing its reads once the token is cancelled
+    ///
+    /// Any streaming conversion reading from it becomes cancellable.
+    pub fn reader<R: Read>(&self, inner: R) -> CancellableReader<R> {
+        CancellableReader {
+            inner,
+            token: self.clone(),
+        }
+    }
+
+    /// `is_cancellation()` tells whether `err` is the error of a cancelled conversion
+    #[must_use]
+    pub fn is_cancellation(err: &io::Error) -> bool {
+        err.get_ref()
+            .is_some_and(|inner| inner.downcast_ref::<Cancelled>().is_some())
+    }
+
+    // Fail once cancelled
+    pub(crate) fn check(&self) -> io::Result<()> {
+        if self.is_cancelled() {
+            trace_event!(debug, "conversion cancelled");
+            Err(io::Error::other(Cancelled))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// `CancellableReader` reads from an inner reader until its `CancellationToken` is cancelled
+///
+/// Built by `CancellationToken::reader()`.
+#[derive(Debug)]
+pub struct CancellableReader<R> {
+    inner: R,
+    token: CancellationToken,
+}
+
+impl<R> CancellableReader<R> {
+    /// `into_inner()` gives the inner reader back
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CancellableReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.token.check()?;
+        self.inner.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copy_decode;
+
+    #[test]
+    fn cancel_between_reads() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        let encoded = crate::encode(&vec![0; 100_000]);
+        let mut reader = token.reader(encoded.as_bytes());
+        let mut buffer = [0; 10];
+        assert_eq!(reader.read(&mut buffer).unwrap(), 10);
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+        let err = copy_decode(&mut reader, &mut io::sink()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert!(CancellationToken::is_cancellation(&err));
+        assert!(!CancellationToken::is_cancellation(
+            &io::ErrorKind::Other.into()
+        ));
+    }
+}
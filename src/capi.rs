@@ -0,0 +1,253 @@
+//! C API, for projects linking the library from C or C++
+//!
+//! Every function works on caller provided buffers and reports the number of bytes written
+//! through an explicit output parameter. The matching declarations are in `include/base85rs.h`.
+
+use std::slice;
+
+use crate::{decode_into, decoded_len, encode_slice, encoded_len, DecodeError, EncodeError};
+
+/// The operation succeeded
+pub const BASE85_OK: i32 = 0;
+/// A required pointer was null
+pub const BASE85_ERROR_NULL_POINTER: i32 = -1;
+/// The output buffer is too small
+pub const BASE85_ERROR_OUTPUT_TOO_SMALL: i32 = -2;
+/// The input contains a character outside of the alphabet
+pub const BASE85_ERROR_INVALID_BYTE: i32 = -3;
+/// A group of 5 characters of the input exceeds 32 bits
+pub const BASE85_ERROR_OVERFLOW: i32 = -4;
+/// The input is malformed in another way
+pub const BASE85_ERROR_INVALID_INPUT: i32 = -5;
+
+// A null pointer is only accepted for an empty buffer.
+unsafe fn input<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if ptr.is_null() {
+        (len == 0).then_some(&[])
+    } else {
+        Some(slice::from_raw_parts(ptr, len))
+    }
+}
+
+unsafe fn output<'a>(ptr: *mut u8, len: usize) -> Option<&'a mut [u8]> {
+    if ptr.is_null() {
+        (len == 0).then_some(&mut [])
+    } else {
+        Some(slice::from_raw_parts_mut(ptr, len))
+    }
+}
+
+/// Returns the number of characters needed to encode `len` bytes
+#[no_mangle]
+pub extern "C" fn base85_encoded_len(len: usize) -> usize {
+    encoded_len(len)
+}
+
+/// Returns the maximum number of bytes produced by decoding `len` characters
+#[no_mangle]
+pub extern "C" fn base85_decoded_len(len: usize) -> usize {
+    decoded_len(len)
+}
+
+/// Encodes `data_len` bytes from `data` into `out`, which can hold `out_len` characters
+///
+/// On success, the number of characters written is stored in `written`. The output isn't
+/// NUL terminated.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `data_len` bytes, `out` valid for writes of `out_len`
+/// bytes and `written` valid for a write. Pointers may only be null for empty buffers.
+#[no_mangle]
+pub unsafe extern "C" fn base85_encode(
+    data: *const u8,
+    data_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    let (Some(data), Some(out), false) = (
+        input(data, data_len),
+        output(out, out_len),
+        written.is_null(),
+    ) else {
+        return BASE85_ERROR_NULL_POINTER;
+    };
+
+    match encode_slice(data, out) {
+        Ok(len) => {
+            *written = len;
+            BASE85_OK
+        }
+        Err(EncodeError::OutputTooSmall) => BASE85_ERROR_OUTPUT_TOO_SMALL,
+        Err(EncodeError::InvalidLength | EncodeError::InvalidMetadata) => {
+            BASE85_ERROR_INVALID_INPUT
+        }
+    }
+}
+
+/// Decodes `instr_len` characters from `instr` into `out`, which can hold `out_len` bytes
+///
+/// On success, the number of bytes written is stored in `written`. When an invalid character
+/// or an overflowing group is found, its index in the input is stored in `written` instead.
+///
+/// # Safety
+///
+/// `instr` must be valid for reads of `instr_len` bytes, `out` valid for writes of `out_len`
+/// bytes and `written` valid for a write. Pointers may only be null for empty buffers.
+#[no_mangle]
+pub unsafe extern "C" fn base85_decode(
+    instr: *const u8,
+    instr_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    let (Some(instr), Some(out), false) = (
+        input(instr, instr_len),
+        output(out, out_len),
+        written.is_null(),
+    ) else {
+        return BASE85_ERROR_NULL_POINTER;
+    };
+
+    match decode_into(instr, out) {
+        Ok(len) => {
+            *written = len;
+            BASE85_OK
+        }
+        Err(DecodeError::InvalidByte { index, .. }) => {
+            *written = index;
+            BASE85_ERROR_INVALID_BYTE
+        }
+        Err(DecodeError::GroupOverflow { index }) => {
+            *written = index;
+            BASE85_ERROR_OVERFLOW
+        }
+        Err(DecodeError::OutputTooSmall) => BASE85_ERROR_OUTPUT_TOO_SMALL,
+        Err(
+            DecodeError::InvalidLength
+            | DecodeError::Overflow
+            | DecodeError::LimitExceeded
+            | DecodeError::InvalidArmor
+            | DecodeError::ChecksumMismatch
+            | DecodeError::InvalidCertificate
+            | DecodeError::UnexpectedEnd
+            | DecodeError::InvalidCompressedData
+            | DecodeError::InvalidUtf8 { .. }
+            | DecodeError::LineTooLong { .. },
+        ) => BASE85_ERROR_INVALID_INPUT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn capi_encode() {
+        let mut out = [0; 7];
+        let mut written = 0;
+        let status = unsafe {
+            base85_encode(
+                b"aaaaa".as_ptr(),
+                5,
+                out.as_mut_ptr(),
+                7,
+                ptr::addr_of_mut!(written),
+            )
+        };
+        assert_eq!(status, BASE85_OK);
+        assert_eq!(&out[..written], b"VPRomVE");
+
+        let status = unsafe {
+            base85_encode(
+                b"aaaaa".as_ptr(),
+                5,
+                out.as_mut_ptr(),
+                6,
+                ptr::addr_of_mut!(written),
+            )
+        };
+        assert_eq!(status, BASE85_ERROR_OUTPUT_TOO_SMALL);
+    }
+
+    #[test]
+    fn capi_decode() {
+        let mut out = [0; 5];
+        let mut written = 0;
+        let status = unsafe {
+            base85_decode(
+                b"VPRomVE".as_ptr(),
+                7,
+                out.as_mut_ptr(),
+                5,
+                ptr::addr_of_mut!(written),
+            )
+        };
+        assert_eq!(status, BASE85_OK);
+        assert_eq!(&out[..written], b"aaaaa");
+
+        let status = unsafe {
+            base85_decode(
+                b"VP]".as_ptr(),
+                3,
+                out.as_mut_ptr(),
+                5,
+                ptr::addr_of_mut!(written),
+            )
+        };
+        assert_eq!(status, BASE85_ERROR_INVALID_BYTE);
+        assert_eq!(written, 2);
+
+        let status = unsafe {
+            base85_decode(
+                b"VPRom|NsC1".as_ptr(),
+                10,
+                out.as_mut_ptr(),
+                5,
+                ptr::addr_of_mut!(written),
+            )
+        };
+        assert_eq!(status, BASE85_ERROR_OVERFLOW);
+        assert_eq!(written, 5);
+        let status = unsafe {
+            base85_decode(
+                b"VPRomVE".as_ptr(),
+                7,
+                out.as_mut_ptr(),
+                4,
+                ptr::addr_of_mut!(written),
+            )
+        };
+        assert_eq!(status, BASE85_ERROR_OUTPUT_TOO_SMALL);
+    }
+
+    #[test]
+    fn capi_null_pointers() {
+        let mut written = 1;
+        let status = unsafe {
+            base85_encode(
+                ptr::null(),
+                0,
+                ptr::null_mut(),
+                0,
+                ptr::addr_of_mut!(written),
+            )
+        };
+        assert_eq!(status, BASE85_OK);
+        assert_eq!(written, 0);
+
+        let status = unsafe {
+            base85_decode(
+                ptr::null(),
+                3,
+                ptr::null_mut(),
+                0,
+                ptr::addr_of_mut!(written),
+            )
+        };
+        assert_eq!(status, BASE85_ERROR_NULL_POINTER);
+    }
+}
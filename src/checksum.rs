@@ -0,0 +1,138 @@
+//! Encoding and decoding with an appended CRC32 of the payload, to detect corrupted or truncated
+//! data
+
+use crate::{decode_vec, encode, DecodeError};
+
+// CRC32 (IEEE 802.3, reflected), as used by zlib and PNG
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| {
+        CRC32_TABLE[usize::from(crc.to_le_bytes()[0] ^ byte)] ^ (crc >> 8)
+    })
+}
+
+// CRC-24, as used by OpenPGP armor (RFC 4880)
+pub(crate) fn crc24(data: &[u8]) -> u32 {
+    data.iter().fold(0x00B7_04CE, |mut crc, &byte| {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= 0x0186_4CFB;
+            }
+        }
+        crc
+    }) & 0x00FF_FFFF
+}
+
+/// `encode_checked()` turns a slice of bytes into a base85 encoded string, followed by the
+/// big-endian CRC32 of the data
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::encode_checked(b"aaaaa"), "VPRomVeYJxxc");
+/// ```
+#[must_use]
+pub fn encode_checked(data: &[u8]) -> String {
+    encode(&append_crc32(data))
+}
+
+// Copy `data` followed by its big-endian CRC32
+pub(crate) fn append_crc32(data: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(data.len() + 4);
+    payload.extend_from_slice(data);
+    payload.extend_from_slice(&crc32(data).to_be_bytes());
+    payload
+}
+
+// Remove and verify the big-endian CRC32 ending `payload`
+pub(crate) fn strip_crc32(mut payload: Vec<u8>) -> Result<Vec<u8>, DecodeError> {
+    let split = payload
+        .len()
+        .checked_sub(4)
+        .ok_or(DecodeError::InvalidLength)?;
+    let checksum = payload.split_off(split);
+    if checksum == crc32(&payload).to_be_bytes() {
+        Ok(payload)
+    } else {
+        Err(DecodeError::ChecksumMismatch)
+    }
+}
+
+/// `decode_checked()` try to decode a string produced by `encode_checked()`, verifying the CRC32
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::InvalidLength` if it is too short to hold a checksum and
+/// `DecodeError::ChecksumMismatch` if the checksum doesn't match the data.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::decode_checked("VPRomVeYJxxc").unwrap(), b"aaaaa");
+/// assert!(base85rs::decode_checked("VPRomVeYJxx").is_err());
+/// ```
+pub fn decode_checked(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    strip_crc32(decode_vec(instr.as_bytes())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn crc24_check_value() {
+        assert_eq!(crc24(b""), 0x00B7_04CE);
+        assert_eq!(crc24(b"123456789"), 0x0021_CF02);
+    }
+
+    #[test]
+    fn checked_roundtrip() {
+        assert_eq!(encode_checked(b""), "00000");
+        for len in 0..16 {
+            let data: Vec<u8> = (0..len).collect();
+            assert_eq!(decode_checked(&encode_checked(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn decode_checked_corrupted() {
+        assert_eq!(
+            decode_checked("VPRomVeYKxxc"),
+            Err(DecodeError::ChecksumMismatch)
+        );
+        assert_eq!(
+            decode_checked("VPRomVeYJx"),
+            Err(DecodeError::ChecksumMismatch)
+        );
+        assert_eq!(decode_checked("VPR"), Err(DecodeError::InvalidLength));
+    }
+}
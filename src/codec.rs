@@ -0,0 +1,1086 @@
+//! Codec objects, assembling the variant, byte order, padding, wrapping, whitespace policy,
+//! framing and checksum options with a builder
+//!
+//! # Example
+//!
+//! ```
+//! use base85rs::{Base85Builder, Whitespace};
+//!
+//! let codec = Base85Builder::new()
+//!     .wrap(5)
+//!     .whitespace(Whitespace::Reject)
+//!     .framing("<", ">")
+//!     .build();
+//! let encoded = codec.encode(b"aaaaa");
+//! assert_eq!(encoded, "<VPRom\nVE>");
+//! assert_eq!(codec.decode(&encoded).unwrap(), b"aaaaa");
+//! ```
+
+use std::borrow::Cow;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::checksum::{append_crc32, strip_crc32};
+use crate::{
+    decode_vec, decode_with_alphabet, encode_u32_chunk, encode_with_alphabet, incremental,
+    Alphabet, DecodeError, RFC1924_ALPHABET,
+};
+
+/// Base85 variant used by a `Codec`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Variant {
+    /// The RFC1924 alphabet of `encode()`
+    #[default]
+    Rfc1924,
+    /// The Z85 alphabet of `z85::encode()`
+    #[cfg(feature = "z85")]
+    Z85,
+    /// The ASCII85 encoding of `ascii85::encode()`
+    #[cfg(feature = "ascii85")]
+    Ascii85,
+    /// The order-preserving alphabet of `ordered::encode()`
+    #[cfg(feature = "ordered")]
+    Ordered,
+}
+
+/// `detect_variant()` guesses the variant `instr` is encoded with, for inputs of mixed sources
+///
+/// The `<~` and `~>` markers, or a `z` between groups of an input fitting in the ASCII85 range,
+/// mean ASCII85. Any of the `.:/[]` characters, in the Z85 alphabet but not in the RFC1924 one,
+/// mean Z85. Other inputs of the RFC1924 alphabet are taken as RFC1924: this is only a guess, as
+/// most short inputs are valid in several variants. Whitespaces are ignored.
+///
+/// Returns `None` for an empty input, an input outside of every alphabet, or when both the ASCII85
+/// and the Z85 markers are found. The order-preserving alphabet is never detected.
+///
+/// # Example
+///
+/// ```
+/// use base85rs::{detect_variant, Variant};
+///
+/// assert_eq!(detect_variant("VPRomVE"), Some(Variant::Rfc1924));
+/// # #[cfg(all(feature = "z85", feature = "ascii85"))] {
+/// assert_eq!(detect_variant("<~@:E_W~>"), Some(Variant::Ascii85));
+/// assert_eq!(detect_variant("Hello.World"), Some(Variant::Z85));
+/// assert_eq!(detect_variant("z0.11"), None);
+/// # }
+/// ```
+#[must_use]
+pub fn detect_variant(instr: &str) -> Option<Variant> {
+    let trimmed = instr.trim();
+    #[cfg(feature = "ascii85")]
+    if trimmed.len() >= 4 && trimmed.starts_with("<~") && trimmed.ends_with("~>") {
+        return Some(Variant::Ascii85);
+    }
+    let chars = || trimmed.bytes().filter(|chr| !chr.is_ascii_whitespace());
+    chars().next()?;
+
+    #[cfg(feature = "ascii85")]
+    let ascii85 = {
+        // `z` stands for a whole group, and can't be found inside one
+        let mut digits = 0;
+        let valid = chars().all(|chr| match chr {
+            b'z' => digits % 5 == 0,
+            b'!'..=b'u' => {
+                digits += 1;
+                true
+            }
+            _ => false,
+        });
+        (valid, valid && chars().any(|chr| chr == b'z'))
+    };
+    #[cfg(not(feature = "ascii85"))]
+    let ascii85 = (false, false);
+    #[cfg(feature = "z85")]
+    let z85 = chars().all(|chr| crate::z85::ALPHABET.contains(&chr))
+        && chars().any(|chr| b".:/[]".contains(&chr));
+    #[cfg(not(feature = "z85"))]
+    let z85 = false;
+
+    match (ascii85, z85) {
+        ((_, true), true) => None,
+        #[cfg(feature = "ascii85")]
+        ((_, true), false) => Some(Variant::Ascii85),
+        #[cfg(feature = "z85")]
+        ((_, false), true) => Some(Variant::Z85),
+        _ if chars().all(crate::is_valid_char) => Some(Variant::Rfc1924),
+        #[cfg(feature = "ascii85")]
+        ((true, _), _) => Some(Variant::Ascii85),
+        _ => None,
+    }
+}
+
+/// Whitespaces accepted by `Codec::decode()`, besides the line breaks of wrapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Whitespace {
+    /// Any whitespace is an invalid byte
+    Reject,
+    /// Spaces are skipped, as by `decode()`
+    #[default]
+    Spaces,
+    /// Spaces, tabs, line breaks and form feeds are skipped
+    Any,
+}
+
+/// Order of the bytes of a group, when read as a 32 bits number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ByteOrder {
+    /// Most significant byte first, as every specified variant
+    #[default]
+    BigEndian,
+    /// Least significant byte first, as some implementations do
+    ///
+    /// Only complete groups are swapped: implementations disagree on the trailing partial group,
+    /// which is kept in big-endian order.
+    LittleEndian,
+}
+
+/// Handling of the trailing partial group, of 1 to 3 bytes
+///
+/// | Padding     | `b"a"` with RFC1924 | `b"a"` with Z85 | Used by                                   |
+/// |-------------|---------------------|-----------------|-------------------------------------------|
+/// | `Truncated` | `VE`                | `ve`            | RFC1924 chunks, ASCII85                   |
+/// | `Zeros`     | `VE_OC`             | `ve{oc`         | `base64.b85encode(pad=True)` of Python    |
+/// | `Counted`   | `3VE_OC`            | `3ve{oc`        | Z85P                                      |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Padding {
+    /// A group of n bytes is encoded with its first n + 1 characters, and decoded by completing it
+    /// with the highest digit
+    #[default]
+    Truncated,
+    /// The group is completed with zero bytes before encoding, decoding returns them
+    Zeros,
+    /// The group is completed with zero bytes, whose count is prepended as a digit and removed
+    /// when decoding. The empty input is encoded as the empty string.
+    Counted,
+}
+
+/// Builder of a `Codec`, every option defaults to the behavior of `encode()` and `decode()`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Base85Builder {
+    codec: Codec,
+}
+
+impl Base85Builder {
+    /// `new()` starts from the default options
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            codec: Codec::RFC1924,
+        }
+    }
+
+    /// `variant()` sets the alphabet and the group handling, replacing any custom alphabet
+    #[must_use]
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.codec.variant = variant;
+        self.codec.alphabet = None;
+        self
+    }
+
+    /// `alphabet()` selects a custom alphabet, with the groups of the RFC1924 variant
+    ///
+    /// The tables of `alphabet` are computed once, and shared by the clones of the codec.
+    #[must_use]
+    pub fn alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.codec.variant = Variant::Rfc1924;
+        self.codec.alphabet = Some(Shared::Counted(Arc::new(alphabet)));
+        self
+    }
+
+    /// `byte_order()` sets the order of the bytes of every group
+    #[must_use]
+    pub fn byte_order(mut self, byte_order: ByteOrder) -> Self {
+        self.codec.byte_order = byte_order;
+        self
+    }
+
+    /// `padding()` sets the handling of the trailing partial group
+    #[must_use]
+    pub fn padding(mut self, padding: Padding) -> Self {
+        self.codec.padding = padding;
+        self
+    }
+
+    /// `wrap()` breaks the encoded text in lines of `width` characters, 0 to disable
+    ///
+    /// The framing isn't counted in the lines.
+    #[must_use]
+    pub fn wrap(mut self, width: usize) -> Self {
+        self.codec.wrap = width;
+        self
+    }
+
+    /// `max_line_length()` makes decoding fail on any line longer than `len` characters, 0 to
+    /// disable
+    ///
+    /// Transports guaranteeing a line length, such as mail gateways, catch corrupted or merged
+    /// lines this way instead of decoding garbage. The framing isn't counted in the lines.
+    #[must_use]
+    pub fn max_line_length(mut self, len: usize) -> Self {
+        self.codec.max_line_length = len;
+        self
+    }
+
+    /// `whitespace()` sets the whitespaces skipped when decoding
+    #[must_use]
+    pub fn whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.codec.whitespace = whitespace;
+        self
+    }
+
+    /// `ignore()` sets characters skipped anywhere when decoding, such as the separators of keys
+    /// written for humans
+    ///
+    /// Only the ASCII characters of `chars` are kept. They are skipped even if they belong to the
+    /// alphabet, so separators must be picked outside of it: the `-` and `_` of RFC1924, or the
+    /// `:` of Z85, are digits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let codec = base85rs::Base85Builder::new().ignore(":.").build();
+    /// assert_eq!(codec.decode("VPR:om.VE").unwrap(), b"aaaaa");
+    /// ```
+    #[must_use]
+    pub fn ignore(mut self, chars: &str) -> Self {
+        let ignored: Vec<u8> = chars.bytes().filter(u8::is_ascii).collect();
+        self.codec.ignored = Shared::Counted(Arc::from(ignored));
+        self
+    }
+
+    /// `framing()` surrounds the encoded text with `prefix` and `suffix`, required when decoding
+    #[must_use]
+    pub fn framing(mut self, prefix: &str, suffix: &str) -> Self {
+        self.codec.prefix = Shared::Counted(Arc::from(prefix));
+        self.codec.suffix = Shared::Counted(Arc::from(suffix));
+        self
+    }
+
+    /// `checksum()` appends the CRC32 of the data before encoding, as `encode_checked()` does,
+    /// and verifies it when decoding
+    #[must_use]
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.codec.checksum = checksum;
+        self
+    }
+
+    /// `build()` returns the configured `Codec`
+    #[must_use]
+    pub fn build(self) -> Codec {
+        self.codec
+    }
+}
+
+/// Immutable encoder and decoder, configured with a `Base85Builder`
+///
+/// A codec is cheap to clone and can be shared across threads. The presets `Codec::RFC1924`,
+/// `Codec::Z85` and `Codec::ASCII85` are constants, usable in statics.
+///
+/// # Example
+///
+/// ```
+/// static CODEC: base85rs::Codec = base85rs::Codec::RFC1924;
+///
+/// assert_eq!(CODEC.encode(b"aaaaa"), "VPRomVE");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Codec {
+    variant: Variant,
+    // Replaces the RFC1924 alphabet when set
+    alphabet: Option<Shared<Alphabet>>,
+    byte_order: ByteOrder,
+    padding: Padding,
+    wrap: usize,
+    max_line_length: usize,
+    whitespace: Whitespace,
+    // ASCII characters skipped when decoding
+    ignored: Shared<[u8]>,
+    prefix: Shared<str>,
+    suffix: Shared<str>,
+    checksum: bool,
+}
+
+impl Codec {
+    /// The codec of `encode()` and `decode()`, also the default one
+    pub const RFC1924: Self = Self::preset(Variant::Rfc1924);
+
+    /// The codec of `z85::encode()` and `z85::decode()`
+    #[cfg(feature = "z85")]
+    pub const Z85: Self = Self::preset(Variant::Z85);
+
+    /// The codec of `ascii85::encode()` and `ascii85::decode()`
+    #[cfg(feature = "ascii85")]
+    pub const ASCII85: Self = Self::preset(Variant::Ascii85);
+
+    const fn preset(variant: Variant) -> Self {
+        Self {
+            variant,
+            alphabet: None,
+            byte_order: ByteOrder::BigEndian,
+            padding: Padding::Truncated,
+            wrap: 0,
+            max_line_length: 0,
+            whitespace: Whitespace::Spaces,
+            ignored: Shared::Static(&[]),
+            prefix: Shared::Static(""),
+            suffix: Shared::Static(""),
+            checksum: false,
+        }
+    }
+
+    // Fail on the first line of `body` longer than `max_line_length`, line breaks excluded
+    fn check_line_lengths(&self, body: &str) -> Result<(), DecodeError> {
+        let mut start = self.prefix.len();
+        for line in body.split('\n') {
+            let len = line.strip_suffix('\r').unwrap_or(line).len();
+            if len > self.max_line_length {
+                return Err(DecodeError::LineTooLong {
+                    index: start + self.max_line_length,
+                });
+            }
+            start += line.len() + 1;
+        }
+        Ok(())
+    }
+
+    /// `encode()` turns a slice of bytes into an encoded `String`
+    #[must_use]
+    pub fn encode(&self, data: &[u8]) -> String {
+        let mut payload = Cow::Borrowed(data);
+        if self.checksum {
+            payload = Cow::Owned(append_crc32(data));
+        }
+        let padding = match self.padding {
+            Padding::Truncated => 0,
+            Padding::Zeros | Padding::Counted => (4 - payload.len() % 4) % 4,
+        };
+        if padding != 0 {
+            let len = payload.len();
+            payload.to_mut().resize(len + padding, 0);
+        }
+        if self.byte_order == ByteOrder::LittleEndian {
+            swap_groups(payload.to_mut());
+        }
+        let mut encoded = self.encode_variant(&payload);
+        if self.padding == Padding::Counted && !payload.is_empty() {
+            encoded.insert(0, char::from(self.chars()[padding]));
+        }
+
+        let mut outdata =
+            String::with_capacity(self.prefix.len() + encoded.len() * 2 + self.suffix.len());
+        outdata.push_str(&self.prefix);
+        for (i, chr) in encoded.chars().enumerate() {
+            if self.wrap != 0 && i != 0 && i % self.wrap == 0 {
+                outdata.push('\n');
+            }
+            outdata.push(chr);
+        }
+        outdata.push_str(&self.suffix);
+        outdata
+    }
+
+    /// `decode()` try to decode a &str produced by `encode()`
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::InvalidArmor` if the framing is missing, `DecodeError::InvalidByte`
+    /// if the input contains a character outside of the alphabet or a rejected whitespace,
+    /// `DecodeError::ChecksumMismatch` if the checksum doesn't match,
+    /// `DecodeError::LineTooLong` if a line exceeds the maximum length, and any other error of the
+    /// decoder of the variant.
+    pub fn decode(&self, instr: &str) -> Result<Vec<u8>, DecodeError> {
+        let body = instr
+            .strip_prefix(&*self.prefix)
+            .and_then(|rest| rest.strip_suffix(&*self.suffix))
+            .ok_or(DecodeError::InvalidArmor)?;
+        if self.max_line_length != 0 {
+            self.check_line_lengths(body)?;
+        }
+
+        // Keep the characters to decode, with their index in `instr`
+        let mut filtered = Vec::with_capacity(body.len());
+        let mut indices = Vec::with_capacity(body.len());
+        for (index, byte) in body.bytes().enumerate() {
+            let index = index + self.prefix.len();
+            let line_break = self.wrap != 0 && matches!(byte, b'\n' | b'\r');
+            let skipped = match self.whitespace {
+                Whitespace::Reject => false,
+                Whitespace::Spaces => byte == b' ',
+                Whitespace::Any => matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | b'\x0c'),
+            };
+            if line_break || skipped || self.ignored.contains(&byte) {
+                continue;
+            }
+            if byte.is_ascii_whitespace() {
+                return Err(DecodeError::InvalidByte { index, byte });
+            }
+            filtered.push(byte);
+            indices.push(index);
+        }
+
+        // The padding count comes first, the groups after it
+        let mut padding = None;
+        if let (Padding::Counted, Some(&byte)) = (self.padding, filtered.first()) {
+            let count = self.chars().iter().position(|&chr| chr == byte);
+            match count {
+                Some(count @ 0..=3) => padding = Some(count),
+                _ => {
+                    return Err(DecodeError::InvalidByte {
+                        index: indices[0],
+                        byte,
+                    })
+                }
+            }
+        }
+        let skipped = usize::from(padding.is_some());
+
+        // Removing ASCII bytes keeps `filtered` valid UTF-8
+        let filtered = String::from_utf8(filtered).unwrap_or_default();
+        let mut decoded = self
+            .decode_variant(&filtered[skipped..])
+            .map_err(|err| match err {
+                DecodeError::InvalidByte { index, byte } => DecodeError::InvalidByte {
+                    index: indices.get(index + skipped).copied().unwrap_or(index),
+                    byte,
+                },
+                DecodeError::GroupOverflow { index } => DecodeError::GroupOverflow {
+                    index: indices.get(index + skipped).copied().unwrap_or(index),
+                },
+                err => err,
+            })?;
+
+        if let Some(padding) = padding {
+            if decoded.is_empty() || decoded.len() % 4 != 0 || decoded.len() < padding {
+                return Err(DecodeError::InvalidLength);
+            }
+        }
+        if self.byte_order == ByteOrder::LittleEndian {
+            swap_groups(&mut decoded);
+        }
+        if let Some(padding) = padding {
+            decoded.truncate(decoded.len() - padding);
+        }
+        if self.checksum {
+            strip_crc32(decoded)
+        } else {
+            Ok(decoded)
+        }
+    }
+
+    // Characters of the alphabet, in the order of their values
+    fn chars(&self) -> &[u8; 85] {
+        if let Some(alphabet) = &self.alphabet {
+            return alphabet.chars();
+        }
+        match self.variant {
+            Variant::Rfc1924 => crate::RFC1924_ALPHABET,
+            #[cfg(feature = "z85")]
+            Variant::Z85 => crate::z85::ALPHABET,
+            #[cfg(feature = "ascii85")]
+            Variant::Ascii85 => crate::ascii85::ALPHABET,
+            #[cfg(feature = "ordered")]
+            Variant::Ordered => crate::ordered::ALPHABET,
+        }
+    }
+
+    fn encode_variant(&self, data: &[u8]) -> String {
+        if let Some(alphabet) = &self.alphabet {
+            return encode_with_alphabet(data, alphabet);
+        }
+        match self.variant {
+            Variant::Rfc1924 => crate::encode(data),
+            #[cfg(feature = "z85")]
+            Variant::Z85 => crate::z85::encode(data),
+            #[cfg(feature = "ascii85")]
+            Variant::Ascii85 => crate::ascii85::encode(data),
+            #[cfg(feature = "ordered")]
+            Variant::Ordered => crate::ordered::encode(data),
+        }
+    }
+
+    fn decode_variant(&self, instr: &str) -> Result<Vec<u8>, DecodeError> {
+        if let Some(alphabet) = &self.alphabet {
+            return decode_with_alphabet(instr, alphabet);
+        }
+        match self.variant {
+            Variant::Rfc1924 => decode_vec(instr.as_bytes()),
+            #[cfg(feature = "z85")]
+            Variant::Z85 => crate::z85::decode(instr),
+            #[cfg(feature = "ascii85")]
+            Variant::Ascii85 => crate::ascii85::decode(instr),
+            #[cfg(feature = "ordered")]
+            Variant::Ordered => crate::ordered::decode(instr),
+        }
+    }
+
+    // Whether converting needs the whole input: the checksum covers all the data, the padding
+    // count comes before the groups, and ASCII85 has no incremental decoder
+    fn needs_whole_input(&self) -> bool {
+        #[cfg(feature = "ascii85")]
+        if self.alphabet.is_none() && self.variant == Variant::Ascii85 {
+            return true;
+        }
+        self.checksum || self.padding == Padding::Counted
+    }
+
+    // Encoder fed in pieces, producing the output of `encode()`, unless it needs the whole input
+    pub(crate) fn stream_encoder(&self) -> Option<StreamEncoder<'_>> {
+        if self.needs_whole_input() {
+            return None;
+        }
+        let mut chars = [0; 256];
+        for (&rfc1924, &chr) in RFC1924_ALPHABET.iter().zip(self.chars()) {
+            chars[usize::from(rfc1924)] = chr;
+        }
+        Some(StreamEncoder {
+            codec: self,
+            chars,
+            tail: [0; 4],
+            filled: 0,
+            started: false,
+            written: 0,
+        })
+    }
+
+    // Decoder fed in pieces, with the output and the errors of `decode()`, unless it needs the
+    // whole input. Errors are the first ones in the order of the input.
+    pub(crate) fn stream_decoder(&self) -> Option<StreamDecoder<'_>> {
+        if self.needs_whole_input() {
+            return None;
+        }
+        let mut digits = [0; 256];
+        for (&chr, &rfc1924) in self.chars().iter().zip(RFC1924_ALPHABET) {
+            digits[usize::from(chr)] = rfc1924;
+        }
+        Some(StreamDecoder {
+            codec: self,
+            digits,
+            decoder: incremental::Decoder::default(),
+            held: Vec::new(),
+            started: false,
+            index: self.prefix.len(),
+            line: (self.prefix.len(), 0),
+        })
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Self::RFC1924
+    }
+}
+
+// Value shared by the clones of a codec, borrowed by the presets
+#[derive(Debug)]
+enum Shared<T: ?Sized + 'static> {
+    Static(&'static T),
+    Counted(Arc<T>),
+}
+
+impl<T: ?Sized> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Static(value) => Self::Static(value),
+            Self::Counted(value) => Self::Counted(Arc::clone(value)),
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            Self::Static(value) => value,
+            Self::Counted(value) => value,
+        }
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for Shared<T> {}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: ?Sized> arbitrary::Arbitrary<'a> for Shared<T>
+where
+    Arc<T>: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Arc::arbitrary(u).map(Self::Counted)
+    }
+}
+
+// Swap the bytes of every complete group, between big-endian and little-endian orders
+fn swap_groups(data: &mut [u8]) {
+    for group in data.chunks_exact_mut(4) {
+        group.reverse();
+    }
+}
+
+// Encoder of `Codec::stream_encoder()`
+pub(crate) struct StreamEncoder<'a> {
+    codec: &'a Codec,
+    // Character of the codec alphabet for every RFC1924 character
+    chars: [u8; 256],
+    tail: [u8; 4],
+    filled: usize,
+    // Whether the prefix was written
+    started: bool,
+    // Characters written, line breaks excluded
+    written: usize,
+}
+
+impl StreamEncoder<'_> {
+    pub(crate) fn update(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        self.start(out);
+        for &byte in data {
+            self.tail[self.filled] = byte;
+            self.filled += 1;
+            if self.filled == 4 {
+                self.filled = 0;
+                self.push_group(self.tail, out);
+            }
+        }
+    }
+
+    pub(crate) fn finish(mut self, out: &mut Vec<u8>) {
+        self.start(out);
+        if self.filled != 0 {
+            if self.codec.padding == Padding::Zeros {
+                self.tail[self.filled..].fill(0);
+                self.push_group(self.tail, out);
+            } else {
+                let mut buffer = [0; 5];
+                let c = encode_u32_chunk(&self.tail[..self.filled], &mut buffer);
+                self.push(&buffer[..=c], out);
+            }
+        }
+        out.extend(self.codec.suffix.as_bytes());
+    }
+
+    fn start(&mut self, out: &mut Vec<u8>) {
+        if !self.started {
+            self.started = true;
+            out.extend(self.codec.prefix.as_bytes());
+        }
+    }
+
+    fn push_group(&mut self, mut group: [u8; 4], out: &mut Vec<u8>) {
+        if self.codec.byte_order == ByteOrder::LittleEndian {
+            group.reverse();
+        }
+        let mut buffer = [0; 5];
+        let _ = encode_u32_chunk(&group, &mut buffer);
+        self.push(&buffer, out);
+    }
+
+    // Write RFC1924 characters with the codec alphabet, wrapping lines
+    fn push(&mut self, encoded: &[u8], out: &mut Vec<u8>) {
+        for &chr in encoded {
+            if self.codec.wrap != 0 && self.written != 0 && self.written % self.codec.wrap == 0 {
+                out.push(b'\n');
+            }
+            out.push(self.chars[usize::from(chr)]);
+            self.written += 1;
+        }
+    }
+}
+
+// Decoder of `Codec::stream_decoder()`
+pub(crate) struct StreamDecoder<'a> {
+    codec: &'a Codec,
+    // RFC1924 character for every character of the codec alphabet, 0 for the others
+    digits: [u8; 256],
+    decoder: incremental::Decoder,
+    // Input not decoded yet: the start of the prefix, then what may be the suffix
+    held: Vec<u8>,
+    // Whether the prefix was read
+    started: bool,
+    // Index in the input of the first byte after `held`
+    index: usize,
+    // Index of the start of the current line, and its length so far
+    line: (usize, usize),
+}
+
+impl StreamDecoder<'_> {
+    pub(crate) fn update(&mut self, instr: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.held.extend(instr);
+        let prefix = self.codec.prefix.as_bytes();
+        if !self.started {
+            if self.held.len() < prefix.len() {
+                return if prefix.starts_with(&self.held) {
+                    Ok(())
+                } else {
+                    Err(DecodeError::InvalidArmor)
+                };
+            }
+            if !self.held.starts_with(prefix) {
+                return Err(DecodeError::InvalidArmor);
+            }
+            self.held.drain(..prefix.len());
+            self.started = true;
+        }
+        // The suffix is only known to be one at the end
+        let Some(len) = self.held.len().checked_sub(self.codec.suffix.len()) else {
+            return Ok(());
+        };
+        let body: Vec<u8> = self.held.drain(..len).collect();
+        self.decode_body(&body, out)
+    }
+
+    pub(crate) fn finish(mut self, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.update(&[], out)?;
+        if !self.started || self.held != self.codec.suffix.as_bytes() {
+            return Err(DecodeError::InvalidArmor);
+        }
+        self.decoder.finish(|bytes| {
+            out.extend(bytes);
+            Ok(())
+        })
+    }
+
+    // Decode the characters between the prefix and the suffix, filtered as by `Codec::decode()`
+    fn decode_body(&mut self, body: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let codec = self.codec;
+        // Characters to decode, ending at `index`
+        let mut run = Vec::with_capacity(body.len());
+        for &byte in body {
+            let index = self.index;
+            self.index += 1;
+            if codec.max_line_length != 0 {
+                self.check_line(index, byte)?;
+            }
+            let line_break = codec.wrap != 0 && matches!(byte, b'\n' | b'\r');
+            let skipped = match codec.whitespace {
+                Whitespace::Reject => false,
+                Whitespace::Spaces => byte == b' ',
+                Whitespace::Any => matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | b'\x0c'),
+            };
+            let digit = self.digits[usize::from(byte)];
+            if line_break || skipped || codec.ignored.contains(&byte) {
+                self.decode_run(&run, index, out)?;
+                run.clear();
+            } else if byte.is_ascii_whitespace() || digit == 0 {
+                self.decode_run(&run, index, out)?;
+                return Err(DecodeError::InvalidByte { index, byte });
+            } else {
+                run.push(digit);
+            }
+        }
+        self.decode_run(&run, self.index, out)
+    }
+
+    // Decode RFC1924 characters, the last one before `end` in the input
+    fn decode_run(&mut self, run: &[u8], end: usize, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let little_endian = self.codec.byte_order == ByteOrder::LittleEndian;
+        self.decoder.set_index(end - run.len());
+        self.decoder.update(run, |group| {
+            if little_endian {
+                out.extend(group.iter().rev());
+            } else {
+                out.extend(group);
+            }
+            Ok(())
+        })
+    }
+
+    // Fail once the current line is longer than `max_line_length`, its line break excluded
+    fn check_line(&mut self, index: usize, byte: u8) -> Result<(), DecodeError> {
+        let max = self.codec.max_line_length;
+        let (start, len) = &mut self.line;
+        if byte == b'\n' {
+            (*start, *len) = (index + 1, 0);
+            return Ok(());
+        }
+        *len += 1;
+        // A `\r` may be the start of a `\r\n` line break
+        if *len > max + 1 || (*len == max + 1 && byte != b'\r') {
+            return Err(DecodeError::LineTooLong {
+                index: *start + max,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_default() {
+        let codec = Base85Builder::new().build();
+        assert_eq!(codec.encode(b"aaaaa"), crate::encode(b"aaaaa"));
+        assert_eq!(codec.decode("VPRom VE").unwrap(), b"aaaaa");
+    }
+
+    #[test]
+    fn codec_max_line_length() {
+        let codec = Base85Builder::new()
+            .wrap(5)
+            .max_line_length(5)
+            .framing("<", ">")
+            .build();
+        let encoded = codec.encode(b"aaaaaaaa");
+        assert_eq!(codec.decode(&encoded).unwrap(), b"aaaaaaaa");
+        assert_eq!(codec.decode("<VPRom\r\nVPRom>").unwrap(), b"aaaaaaaa");
+        // Two lines merged together
+        assert_eq!(
+            codec.decode("<VPRom\nVPRomVPRom>"),
+            Err(DecodeError::LineTooLong { index: 12 })
+        );
+        let unlimited = Base85Builder::new().wrap(5).build();
+        assert_eq!(unlimited.decode("VPRomVPRom").unwrap(), b"aaaaaaaa");
+    }
+
+    #[test]
+    fn codec_presets() {
+        assert_eq!(Codec::RFC1924, Codec::default());
+        assert_eq!(Codec::RFC1924, Base85Builder::new().build());
+        #[cfg(feature = "z85")]
+        assert_eq!(
+            Codec::Z85,
+            Base85Builder::new().variant(Variant::Z85).build()
+        );
+        #[cfg(feature = "ascii85")]
+        assert_eq!(Codec::ASCII85.encode(b"\0\0\0\0abc"), "z@:E^");
+        // Framing compares by value, static or not
+        let framed = Base85Builder::new().framing("", "").build();
+        assert_eq!(framed, Codec::RFC1924);
+    }
+
+    #[test]
+    fn codec_checksum() {
+        let codec = Base85Builder::new().checksum(true).build();
+        assert_eq!(codec.encode(b"aaaaa"), crate::encode_checked(b"aaaaa"));
+        assert_eq!(codec.decode("VPRomVeYJxxc").unwrap(), b"aaaaa");
+        assert_eq!(
+            codec.decode("VPRomWeYJxxc"),
+            Err(DecodeError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn codec_wrap_and_framing() {
+        let codec = Base85Builder::new().wrap(3).framing("[", "]\n").build();
+        let encoded = codec.encode(b"aaaaa");
+        assert_eq!(encoded, "[VPR\nomV\nE]\n");
+        assert_eq!(codec.decode(&encoded).unwrap(), b"aaaaa");
+        assert_eq!(codec.decode("VPRomVE"), Err(DecodeError::InvalidArmor));
+        // Indices are relative to the whole input
+        assert_eq!(
+            codec.decode("[VPR\nom\"\nE]\n"),
+            Err(DecodeError::InvalidByte {
+                index: 7,
+                byte: b'"'
+            })
+        );
+    }
+
+    #[test]
+    fn codec_byte_order() {
+        let codec = Base85Builder::new()
+            .byte_order(ByteOrder::LittleEndian)
+            .build();
+        assert_eq!(codec.encode(b"\x01\0\0\0"), crate::encode(b"\0\0\0\x01"));
+        assert_eq!(codec.encode(b"abcdefg"), crate::encode(b"dcbaefg"));
+        assert_eq!(
+            codec.decode(&crate::encode(b"dcbaefg")).unwrap(),
+            b"abcdefg"
+        );
+
+        let checked = Base85Builder::new()
+            .byte_order(ByteOrder::LittleEndian)
+            .checksum(true)
+            .build();
+        assert_eq!(
+            checked.decode(&checked.encode(b"abcdefg")).unwrap(),
+            b"abcdefg"
+        );
+    }
+
+    #[test]
+    fn codec_padding_vectors() {
+        let zeros = Base85Builder::new().padding(Padding::Zeros).build();
+        assert_eq!(zeros.encode(b"a"), "VE_OC");
+        assert_eq!(zeros.encode(b"a"), crate::encode_padded(b"a"));
+        assert_eq!(zeros.decode("VE_OC").unwrap(), b"a\0\0\0");
+
+        let counted = Base85Builder::new().padding(Padding::Counted).build();
+        assert_eq!(counted.encode(b"a"), "3VE_OC");
+        assert_eq!(counted.encode(b"aaaa"), "0VPRom");
+        assert_eq!(counted.encode(b""), "");
+        for len in 0..10 {
+            let data = vec![0xA5; len];
+            assert_eq!(counted.decode(&counted.encode(&data)).unwrap(), data);
+        }
+        assert_eq!(
+            counted.decode("4VE_OC"),
+            Err(DecodeError::InvalidByte {
+                index: 0,
+                byte: b'4'
+            })
+        );
+        assert_eq!(counted.decode("0"), Err(DecodeError::InvalidLength));
+        assert_eq!(counted.decode("3VE"), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            counted.decode("3V\"_OC"),
+            Err(DecodeError::InvalidByte {
+                index: 2,
+                byte: b'"'
+            })
+        );
+        assert_eq!(
+            counted.decode("0VPRom |NsC1"),
+            Err(DecodeError::GroupOverflow { index: 7 })
+        );
+
+        let little = Base85Builder::new()
+            .padding(Padding::Counted)
+            .byte_order(ByteOrder::LittleEndian)
+            .checksum(true)
+            .build();
+        assert_eq!(little.decode(&little.encode(b"abcde")).unwrap(), b"abcde");
+    }
+
+    #[cfg(all(feature = "z85", feature = "ascii85"))]
+    #[test]
+    fn codec_padding_variants() {
+        let z85p = Base85Builder::new()
+            .variant(Variant::Z85)
+            .padding(Padding::Counted)
+            .build();
+        // The vectors of `z85p::encode()`
+        assert_eq!(z85p.encode(b"a"), "3ve{oc");
+        assert_eq!(z85p.encode(b"aaaaa"), "3vprOMve{oc");
+        assert_eq!(z85p.decode("3vprOMve{oc").unwrap(), b"aaaaa");
+
+        let ascii85 = Base85Builder::new().variant(Variant::Ascii85);
+        assert_eq!(ascii85.clone().build().encode(b"a"), "@/");
+        assert_eq!(
+            ascii85.padding(Padding::Zeros).build().encode(b"a"),
+            "@/p9-"
+        );
+    }
+
+    #[test]
+    fn codec_whitespace() {
+        let reject = Base85Builder::new().whitespace(Whitespace::Reject).build();
+        assert_eq!(
+            reject.decode("VPRom VE"),
+            Err(DecodeError::InvalidByte {
+                index: 5,
+                byte: b' '
+            })
+        );
+
+        let any = Base85Builder::new().whitespace(Whitespace::Any).build();
+        assert_eq!(any.decode("VPRom\r\n\tVE").unwrap(), b"aaaaa");
+        assert!(Base85Builder::new().build().decode("VPRom\nVE").is_err());
+    }
+
+    #[test]
+    fn codec_ignored_chars() {
+        let codec = Base85Builder::new()
+            .whitespace(Whitespace::Reject)
+            .ignore(",.\té")
+            .build();
+        assert_eq!(codec.decode("VP.Ro,m\tVE").unwrap(), b"aaaaa");
+        assert_eq!(
+            codec.decode("VP.R]om"),
+            Err(DecodeError::InvalidByte {
+                index: 4,
+                byte: b']'
+            })
+        );
+        assert!(codec.decode("VPRom VE").is_err());
+        assert!(codec.decode("VPRoméVE").is_err());
+        // The default codec doesn't ignore anything besides spaces
+        assert!(Codec::RFC1924.decode("VP.RomVE").is_err());
+
+        #[cfg(feature = "z85")]
+        {
+            let z85 = Base85Builder::new()
+                .variant(Variant::Z85)
+                .ignore("_")
+                .build();
+            assert_eq!(
+                z85.decode("Hello_World").unwrap(),
+                [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B]
+            );
+        }
+    }
+
+    #[test]
+    fn codec_custom_alphabet_shared() {
+        let codec = Base85Builder::new().alphabet(Alphabet::SHELL_SAFE).build();
+        let handles: Vec<_> = (0..4_u8)
+            .map(|i| {
+                let codec = codec.clone();
+                std::thread::spawn(move || {
+                    let data = vec![0xFF; usize::from(i) * 3];
+                    assert_eq!(codec.decode(&codec.encode(&data)).unwrap(), data);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(codec.encode(b"\xff"), "^^");
+    }
+
+    #[cfg(all(feature = "z85", feature = "ascii85"))]
+    #[test]
+    fn variant_detection() {
+        let data = b"pollenless\0\0\0\0";
+        assert_eq!(detect_variant(&crate::encode(data)), Some(Variant::Rfc1924));
+        assert_eq!(
+            detect_variant(&crate::ascii85::encode(data)),
+            Some(Variant::Ascii85)
+        );
+        assert_eq!(detect_variant(" <~9jqo^~>\n"), Some(Variant::Ascii85));
+        assert_eq!(detect_variant("[ab]"), Some(Variant::Z85));
+        // Outside of the RFC1924 and Z85 alphabets
+        assert_eq!(detect_variant("@:E_\"\\"), Some(Variant::Ascii85));
+        // A `z` inside a group isn't ASCII85
+        assert_eq!(detect_variant("VPRoz"), Some(Variant::Rfc1924));
+
+        // Both markers, or none of the alphabets
+        for ambiguous in ["z0.11", "9jqo^z[", "", " \n ", "VP\"R~", "caf\u{e9}"] {
+            assert_eq!(detect_variant(ambiguous), None, "{ambiguous:?}");
+        }
+    }
+
+    #[cfg(all(feature = "z85", feature = "ascii85"))]
+    #[test]
+    fn codec_variants() {
+        let z85 = Base85Builder::new().variant(Variant::Z85).build();
+        assert_eq!(z85.encode(b"aaaaa"), "vprOMve");
+        let ascii85 = Base85Builder::new()
+            .variant(Variant::Ascii85)
+            .whitespace(Whitespace::Reject)
+            .build();
+        assert_eq!(ascii85.encode(b"\0\0\0\0abc"), "z@:E^");
+        assert!(ascii85.decode("z@:E ^").is_err());
+    }
+}
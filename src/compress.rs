@@ -0,0 +1,92 @@
+//! Zlib compression chained with the RFC1924 codec, a common way to embed payloads in text
+
+use std::io::{self, Read, Write};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::{DecodeError, DecoderReader, EncoderWriter, PositionedError};
+
+/// `encode_compressed()` compresses `data` with zlib and encodes the compressed stream
+///
+/// Compressed chunks are encoded as the compressor produces them, in a single pass.
+///
+/// # Example
+///
+/// ```
+/// let encoded = base85rs::encode_compressed(&[b'a'; 1000]);
+/// assert!(encoded.len() < 40);
+/// assert_eq!(base85rs::decode_decompressed(&encoded).unwrap(), [b'a'; 1000]);
+/// ```
+#[must_use]
+pub fn encode_compressed(data: &[u8]) -> String {
+    let mut compressor = ZlibEncoder::new(EncoderWriter::new(Vec::new()), Compression::default());
+    // Writing to a `Vec` never fails
+    let outdata = compressor
+        .write_all(data)
+        .and_then(|()| compressor.finish())
+        .and_then(|mut encoder| encoder.finish())
+        .unwrap_or_default();
+    String::from_utf8(outdata).unwrap_or_default()
+}
+
+/// `decode_decompressed()` try to decode a &str produced by `encode_compressed()` and
+/// decompresses the decoded zlib stream
+///
+/// Decoded chunks are decompressed as the decoder produces them, in a single pass.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX` and
+/// `DecodeError::InvalidCompressedData` if the decoded data isn't a valid zlib stream.
+pub fn decode_decompressed(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut decompressor = ZlibDecoder::new(DecoderReader::new(instr.as_bytes()));
+    let mut outdata = Vec::new();
+    decompressor
+        .read_to_end(&mut outdata)
+        .map_err(|err| decode_error(&err))?;
+    Ok(outdata)
+}
+
+// Recover the error of the decoder, anything else comes from the decompressor
+fn decode_error(err: &io::Error) -> DecodeError {
+    err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<PositionedError>())
+        .map_or(DecodeError::InvalidCompressedData, |err| err.error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn compressed_roundtrip() {
+        let data: Vec<u8> = (0..100_000_u32).map(|i| (i % 10) as u8).collect();
+        let encoded = encode_compressed(&data);
+        assert!(encoded.len() < data.len() / 10);
+        assert_eq!(decode_decompressed(&encoded).unwrap(), data);
+        assert_eq!(decode_decompressed(&encode_compressed(b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn compressed_errors() {
+        assert_eq!(
+            decode_decompressed(&encode(b"not zlib")),
+            Err(DecodeError::InvalidCompressedData)
+        );
+        let mut encoded = encode_compressed(b"aaaaa").into_bytes();
+        encoded[9] = b']';
+        assert_eq!(
+            decode_decompressed(std::str::from_utf8(&encoded).unwrap()),
+            Err(DecodeError::InvalidByte {
+                index: 9,
+                byte: b']'
+            })
+        );
+        // The compressed stream itself is plain RFC1924
+        assert!(decode(encode_compressed(b"aaaaa")).is_some());
+    }
+}
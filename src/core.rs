@@ -0,0 +1,33 @@
+//! Sans-io codec, for protocol libraries embedding it in their own buffering or event loop
+//!
+//! These are pure state machines over slices: they never read, write nor allocate, and don't
+//! depend on `std::io`, `String` nor `Vec`. The caller feeds its input in arbitrary sized pieces
+//! and receives the output through a sink, group by group, as soon as it is available.
+//!
+//! - `encode_group()` / `decode_group()` convert a single 4 bytes / 5 characters group.
+//! - `Encoder` / `Decoder` convert a whole message, keeping the trailing partial group between
+//!   pieces.
+//!
+//! Everything else in the crate is built on top of them.
+//!
+//! # Example
+//!
+//! ```
+//! use base85rs::core::{decode_group, encode_group, Encoder};
+//!
+//! assert_eq!(&encode_group(*b"aaaa"), b"VPRom");
+//! assert_eq!(decode_group(*b"VPRom"), Ok(*b"aaaa"));
+//!
+//! // One packet of a framed protocol, encoded without any intermediate buffer
+//! let mut frame = [0; 16];
+//! let mut len = 0;
+//! let mut encoder = Encoder::default();
+//! encoder.update(b"aaaaa", |chars| {
+//!     frame[len..len + chars.len()].copy_from_slice(chars);
+//!     len += chars.len();
+//! });
+//! assert_eq!(&frame[..len], b"VPRom");
+//! ```
+
+pub use crate::incremental::{Decoder, Encoder};
+pub use crate::{decode_group, encode_group};
@@ -0,0 +1,299 @@
+//! Constant-time encoding and decoding, for secret material such as private keys or tokens
+//!
+//! Unlike `encode()` / `decode()`, characters are mapped without any table indexed by secret data
+//! and the input is always processed until its end, so that the running time only depends on the
+//! input length. Comparisons and selections go through `subtle` to keep the compiler from
+//! introducing branches.
+//!
+//! Skipping whitespaces would make the timing depend on where they are, so they are rejected as
+//! any other character outside of the alphabet.
+//!
+//! # Example
+//!
+//! ```
+//! let encoded = base85rs::ct::encode(b"aaaaa");
+//! assert_eq!(encoded, "VPRomVE");
+//! assert_eq!(base85rs::ct::decode(&encoded).unwrap(), b"aaaaa");
+//! ```
+
+use std::fmt;
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater};
+
+use crate::{encoded_len, DecodeError, RFC1924_ALPHABET};
+
+// Map a digit to its character by scanning the whole alphabet.
+fn encode_digit(digit: u8) -> u8 {
+    let mut chr = 0;
+    for (value, &candidate) in (0..).zip(RFC1924_ALPHABET.iter()) {
+        chr.conditional_assign(&candidate, digit.ct_eq(&value));
+    }
+    chr
+}
+
+// Map a character to its digit by scanning the whole alphabet, along with its validity.
+fn decode_char(chr: u8) -> (u8, Choice) {
+    let mut digit = 0;
+    let mut valid = Choice::from(0);
+    for (value, &candidate) in (0..).zip(RFC1924_ALPHABET.iter()) {
+        let is_candidate = chr.ct_eq(&candidate);
+        digit.conditional_assign(&value, is_candidate);
+        valid |= is_candidate;
+    }
+    (digit, valid)
+}
+
+fn encode_group(group: [u8; 4], outdata: &mut Vec<u8>, len: usize) {
+    let mut value = u32::from_be_bytes(group);
+    let mut chars = [0; 5];
+    for chr in chars.iter_mut().rev() {
+        // Division by a constant is compiled into a multiplication, it doesn't depend on data
+        *chr = encode_digit(u8::try_from(value % 85).unwrap_or_default());
+        value /= 85;
+    }
+    outdata.extend(&chars[..=len]);
+}
+
+/// `encode()` turns a slice of bytes into base85 encoded `String` in constant time
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::ct::encode(b"a"), "VE");
+/// ```
+#[must_use]
+pub fn encode(data: &[u8]) -> String {
+    let mut outdata = Vec::with_capacity(encoded_len(data.len()));
+    for chunk in data.chunks(4) {
+        let mut group = [0; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        encode_group(group, &mut outdata, chunk.len());
+    }
+    outdata.into_iter().map(char::from).collect()
+}
+
+/// `decode()` try to decode a base85 encoded &str in constant time
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` for the first character outside of the alphabet and
+/// `DecodeError::GroupOverflow` for the first group of 5 characters exceeding `u32::MAX`. In both
+/// cases the whole input has been processed before returning.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::ct::decode("VE").unwrap(), b"a");
+/// assert!(base85rs::ct::decode("V E").is_err());
+/// ```
+pub fn decode(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    let instr = instr.as_bytes();
+    let mut outdata = Vec::with_capacity(crate::decoded_len(instr.len()));
+
+    let mut invalid = Choice::from(0);
+    let mut invalid_index = 0_u64;
+    let mut invalid_byte = 0_u8;
+    let mut overflow = Choice::from(0);
+    let mut overflow_index = 0_u64;
+
+    for (chunk_index, chunk) in instr.chunks(5).enumerate() {
+        // The trailing partial group is completed with the highest digit
+        let mut value = 0_u64;
+        for position in 0..5 {
+            let (digit, valid) = chunk.get(position).map_or((84, Choice::from(1)), |&chr| {
+                let (digit, valid) = decode_char(chr);
+                let first = !valid & !invalid;
+                let index = u64::try_from(chunk_index * 5 + position).unwrap_or_default();
+                invalid_index.conditional_assign(&index, first);
+                invalid_byte.conditional_assign(&chr, first);
+                (digit, valid)
+            });
+            invalid |= !valid;
+            value = value * 85 + u64::from(digit);
+        }
+        // A single trailing character can't encode any byte, as with `decode()` it is
+        // only checked for validity
+        let overflows = value.ct_gt(&u64::from(u32::MAX)) & Choice::from(u8::from(chunk.len() > 1));
+        let index = u64::try_from(chunk_index * 5).unwrap_or_default();
+        overflow_index.conditional_assign(&index, overflows & !overflow);
+        overflow |= overflows;
+
+        let bytes = u32::try_from(value & u64::from(u32::MAX))
+            .unwrap_or_default()
+            .to_be_bytes();
+        outdata.extend(&bytes[..chunk.len() - 1]);
+    }
+
+    if bool::from(invalid) {
+        Err(DecodeError::InvalidByte {
+            index: usize::try_from(invalid_index).unwrap_or_default(),
+            byte: invalid_byte,
+        })
+    } else if bool::from(overflow) {
+        Err(DecodeError::GroupOverflow {
+            index: usize::try_from(overflow_index).unwrap_or_default(),
+        })
+    } else {
+        Ok(outdata)
+    }
+}
+
+/// `EncodedSecret` holds a base85 encoded secret, such as an API token, compared in constant time
+///
+/// Comparing with `==`, to another `EncodedSecret` or to a `&str`, takes the same time wherever
+/// the characters differ, only the lengths being compared upfront. `Debug` doesn't print the
+/// secret, and with the `zeroize` feature it is wiped from memory on drop.
+///
+/// # Example
+///
+/// ```
+/// let secret = base85rs::ct::EncodedSecret::encode(b"aaaaa");
+/// assert!(secret == *"VPRomVE");
+/// assert!(secret != *"VPRomVF");
+/// assert_eq!(format!("{secret:?}"), "EncodedSecret(<redacted>)");
+/// ```
+#[derive(Clone)]
+pub struct EncodedSecret(String);
+
+impl EncodedSecret {
+    /// `new()` wraps an already encoded secret
+    #[must_use]
+    pub fn new(encoded: String) -> Self {
+        Self(encoded)
+    }
+
+    /// `encode()` encodes `data` in constant time, wrapping the result
+    #[must_use]
+    pub fn encode(data: &[u8]) -> Self {
+        Self(encode(data))
+    }
+
+    /// `decode()` decodes the secret in constant time
+    ///
+    /// # Errors
+    ///
+    /// Same as `ct::decode()`.
+    pub fn decode(&self) -> Result<Vec<u8>, DecodeError> {
+        decode(&self.0)
+    }
+
+    /// `expose()` gives access to the encoded secret, to send or store it
+    #[must_use]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for EncodedSecret {
+    fn from(encoded: String) -> Self {
+        Self(encoded)
+    }
+}
+
+impl ConstantTimeEq for EncodedSecret {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.as_bytes().ct_eq(other.0.as_bytes())
+    }
+}
+
+impl PartialEq for EncodedSecret {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for EncodedSecret {}
+
+impl PartialEq<str> for EncodedSecret {
+    fn eq(&self, other: &str) -> bool {
+        self.0.as_bytes().ct_eq(other.as_bytes()).into()
+    }
+}
+
+impl fmt::Debug for EncodedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncodedSecret(<redacted>)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for EncodedSecret {
+    fn drop(&mut self) {
+        ::zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_matches_default_encoder() {
+        let wordlist = [
+            "",
+            "a",
+            "aa",
+            "aaa",
+            "aaaa",
+            "relimitation",
+            "countercompetition",
+        ];
+        for word in wordlist {
+            assert_eq!(encode(word.as_bytes()), crate::encode(word.as_bytes()));
+        }
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(encode(&data), crate::encode(&data));
+    }
+
+    #[test]
+    fn decode_matches_default_decoder() {
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = crate::encode(&data);
+        assert_eq!(decode(&encoded).unwrap(), data);
+        for len in 0..encoded.len() {
+            assert_eq!(decode(&encoded[..len]).ok(), crate::decode(&encoded[..len]));
+        }
+    }
+
+    #[test]
+    fn decode_reports_first_invalid() {
+        assert_eq!(
+            decode("VPRom]"),
+            Err(DecodeError::InvalidByte {
+                index: 5,
+                byte: b']'
+            })
+        );
+        assert_eq!(
+            decode("VPR]m V"),
+            Err(DecodeError::InvalidByte {
+                index: 3,
+                byte: b']'
+            })
+        );
+    }
+
+    #[test]
+    fn decode_overflow() {
+        assert_eq!(decode("|NsC0"), Ok(vec![0xff; 4]));
+        assert_eq!(
+            decode("|NsC1"),
+            Err(DecodeError::GroupOverflow { index: 0 })
+        );
+        assert_eq!(
+            decode("VPRom|NsC1|NsC1"),
+            Err(DecodeError::GroupOverflow { index: 5 })
+        );
+    }
+
+    #[test]
+    fn encoded_secret() {
+        let secret = EncodedSecret::encode(&[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(secret.expose(), crate::encode(&[0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(secret.decode().unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(secret, EncodedSecret::from(secret.expose().to_string()));
+        assert_ne!(secret, EncodedSecret::new(String::from("00000")));
+        assert!(secret != *"");
+        assert!(!format!("{secret:?}").contains(secret.expose()));
+    }
+}
@@ -0,0 +1,65 @@
+//! Conversion helpers for the `data-encoding` ecosystem
+//!
+//! `data_encoding::Encoding` only describes power of two bases, so base85 can't be expressed as a
+//! `Specification`. These helpers convert between any `Encoding` and base85 instead, letting code
+//! built around that abstraction hand data over to and from this crate.
+//!
+//! # Example
+//!
+//! ```
+//! use data_encoding::HEXLOWER;
+//!
+//! let encoded = base85rs::data_encoding::from_encoding(&HEXLOWER, b"6161616161").unwrap();
+//! assert_eq!(encoded, "VPRomVE");
+//! let hex = base85rs::data_encoding::to_encoding(&HEXLOWER, &encoded).unwrap();
+//! assert_eq!(hex, "6161616161");
+//! ```
+
+use ::data_encoding::Encoding;
+
+use crate::{decode_vec, encode, DecodeError};
+
+/// `from_encoding()` decodes `input` with `encoding` and encodes the result in base85
+///
+/// # Errors
+///
+/// Returns the `data_encoding::DecodeError` raised when `input` isn't valid for `encoding`.
+pub fn from_encoding(
+    encoding: &Encoding,
+    input: &[u8],
+) -> Result<String, ::data_encoding::DecodeError> {
+    encoding.decode(input).map(|data| encode(&data))
+}
+
+/// `to_encoding()` decodes base85 `instr` and encodes the result with `encoding`
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet
+/// and `DecodeError::GroupOverflow` if a group exceeds `u32::MAX`.
+pub fn to_encoding(encoding: &Encoding, instr: &str) -> Result<String, DecodeError> {
+    decode_vec(instr.as_bytes()).map(|data| encoding.encode(&data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::data_encoding::{BASE64, HEXUPPER};
+
+    #[test]
+    fn from_base64() {
+        assert_eq!(
+            from_encoding(&BASE64, b"cG9sbGVubGVzcw==").unwrap(),
+            "aBpmEWo~R`b8`"
+        );
+        assert!(from_encoding(&BASE64, b"cG9sbGVubGVzcw").is_err());
+    }
+
+    #[test]
+    fn to_hex() {
+        assert_eq!(
+            to_encoding(&HEXUPPER, "aBpmEWo~R`b8`").unwrap(),
+            "706F6C6C656E6C657373"
+        );
+    }
+}
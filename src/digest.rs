@@ -0,0 +1,121 @@
+//! Hashing the raw bytes going through the streaming encoder and decoder
+
+use std::io::{self, Read, Write};
+
+use ::digest::{Digest, Output};
+
+/// `DigestWriter` updates a digest with the bytes written to an inner writer
+///
+/// Wrapping an `EncoderWriter` hashes the raw data in the same pass as encoding it.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Write;
+/// use sha2::{Digest, Sha256};
+///
+/// let encoder = base85rs::EncoderWriter::new(Vec::new());
+/// let mut writer = base85rs::DigestWriter::new(encoder, Sha256::new());
+/// writer.write_all(b"aaaaa").unwrap();
+/// let (mut encoder, hash) = writer.finalize();
+/// assert_eq!(encoder.finish().unwrap(), b"VPRomVE");
+/// assert_eq!(hash, Sha256::digest(b"aaaaa"));
+/// ```
+#[derive(Debug)]
+pub struct DigestWriter<W, D> {
+    inner: W,
+    digest: D,
+}
+
+impl<W: Write, D: Digest> DigestWriter<W, D> {
+    /// `new()` wraps `inner`, hashing what it accepts with `digest`
+    pub fn new(inner: W, digest: D) -> Self {
+        Self { inner, digest }
+    }
+
+    /// `finalize()` gives the inner writer back, along with the hash of everything written
+    pub fn finalize(self) -> (W, Output<D>) {
+        (self.inner, self.digest.finalize())
+    }
+}
+
+impl<W: Write, D: Digest> Write for DigestWriter<W, D> {
+    /// Writes `data` to the inner writer, hashing only the bytes it accepted
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(data)?;
+        self.digest.update(&data[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// `DigestReader` updates a digest with the bytes read from an inner reader
+///
+/// Wrapping a `DecoderReader` hashes the decoded data in the same pass as decoding it.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Read;
+/// use sha2::{Digest, Sha256};
+///
+/// let decoder = base85rs::DecoderReader::new("VPRomVE".as_bytes());
+/// let mut reader = base85rs::DigestReader::new(decoder, Sha256::new());
+/// let mut decoded = Vec::new();
+/// reader.read_to_end(&mut decoded).unwrap();
+/// assert_eq!(reader.finalize().1, Sha256::digest(&decoded));
+/// ```
+#[derive(Debug)]
+pub struct DigestReader<R, D> {
+    inner: R,
+    digest: D,
+}
+
+impl<R: Read, D: Digest> DigestReader<R, D> {
+    /// `new()` wraps `inner`, hashing what is read from it with `digest`
+    pub fn new(inner: R, digest: D) -> Self {
+        Self { inner, digest }
+    }
+
+    /// `finalize()` gives the inner reader back, along with the hash of everything read
+    pub fn finalize(self) -> (R, Output<D>) {
+        (self.inner, self.digest.finalize())
+    }
+}
+
+impl<R: Read, D: Digest> Read for DigestReader<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.digest.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, DecoderReader, EncoderWriter};
+    use sha2::Sha256;
+
+    #[test]
+    fn digest_while_encoding_and_decoding() {
+        let data: Vec<u8> = (0..100_000_u32).map(|i| (i % 251) as u8).collect();
+        let expected = Sha256::digest(&data);
+
+        let mut writer = DigestWriter::new(EncoderWriter::new(Vec::new()), Sha256::new());
+        for chunk in data.chunks(777) {
+            writer.write_all(chunk).unwrap();
+        }
+        let (mut encoder, hash) = writer.finalize();
+        let text = encoder.finish().unwrap();
+        assert_eq!(text, encode(&data).as_bytes());
+        assert_eq!(hash, expected);
+
+        let mut reader = DigestReader::new(DecoderReader::new(text.as_slice()), Sha256::new());
+        io::copy(&mut reader, &mut io::sink()).unwrap();
+        assert_eq!(reader.finalize().1, expected);
+    }
+}
@@ -0,0 +1,199 @@
+use core::fmt;
+
+/// Errors returned by the fallible encoding functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// The output buffer is too small to hold the encoded data
+    OutputTooSmall,
+    /// The input length isn't a multiple of the group size
+    InvalidLength,
+    /// A metadata name or value of a certificate can't be written in ZPL
+    InvalidMetadata,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutputTooSmall => write!(f, "output buffer is too small"),
+            Self::InvalidLength => write!(f, "input length is not a multiple of the group size"),
+            Self::InvalidMetadata => write!(f, "metadata can't be written in ZPL"),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
+
+/// Errors returned by `Alphabet::new()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AlphabetError {
+    /// The alphabet contains a space, a control or a non-ASCII character
+    InvalidChar(u8),
+    /// The alphabet contains a character twice
+    DuplicateChar(u8),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChar(chr) => write!(f, "invalid alphabet character 0x{chr:02x}"),
+            Self::DuplicateChar(chr) => write!(f, "duplicate alphabet character 0x{chr:02x}"),
+        }
+    }
+}
+
+impl core::error::Error for AlphabetError {}
+
+/// Errors returned by the fallible decoding functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// A byte outside of the base85 alphabet was found at `index` in the input
+    InvalidByte { index: usize, byte: u8 },
+    /// The input doesn't decode to the expected number of bytes
+    InvalidLength,
+    /// The decoded number doesn't fit in the integer type of `decode_u32()`, `decode_u64()` or
+    /// `decode_u128()`, or in the 128 bits of an IPv6 address or a UUID
+    Overflow,
+    /// The output buffer is too small to hold the decoded data
+    OutputTooSmall,
+    /// The decoded data exceeds the size limit set by the caller
+    LimitExceeded,
+    /// The armor lines around the encoded data are missing or malformed
+    InvalidArmor,
+    /// The checksum doesn't match the decoded data
+    ChecksumMismatch,
+    /// The input isn't a valid certificate file
+    InvalidCertificate,
+    /// The input ended before its end marker
+    UnexpectedEnd,
+    /// The decoded data isn't a valid compressed stream
+    InvalidCompressedData,
+    /// The decoded data isn't valid UTF-8 text, past its first `valid_up_to` bytes
+    InvalidUtf8 { valid_up_to: usize },
+    /// A line of the input exceeds the maximum length, at `index` in the input
+    LineTooLong { index: usize },
+    /// A group of 5 characters, starting at `index` in the input, exceeds `u32::MAX`
+    GroupOverflow { index: usize },
+}
+
+impl DecodeError {
+    // Shift the reported index, for errors found in a part of a larger input
+    pub(crate) fn offset_by(self, offset: usize) -> Self {
+        match self {
+            Self::InvalidByte { index, byte } => Self::InvalidByte {
+                index: index + offset,
+                byte,
+            },
+            Self::GroupOverflow { index } => Self::GroupOverflow {
+                index: index + offset,
+            },
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidByte { index, byte } => {
+                write!(f, "invalid byte 0x{byte:02x} at index {index}")
+            }
+            Self::InvalidLength => write!(f, "invalid input length"),
+            Self::Overflow => write!(f, "decoded value overflows"),
+            Self::OutputTooSmall => write!(f, "output buffer is too small"),
+            Self::LimitExceeded => write!(f, "decoded data exceeds the size limit"),
+            Self::InvalidArmor => write!(f, "missing or malformed armor lines"),
+            Self::ChecksumMismatch => write!(f, "checksum mismatch"),
+            Self::InvalidCertificate => write!(f, "invalid certificate file"),
+            Self::UnexpectedEnd => write!(f, "missing end marker"),
+            Self::InvalidCompressedData => write!(f, "invalid compressed data"),
+            Self::InvalidUtf8 { valid_up_to } => {
+                write!(f, "invalid UTF-8 in decoded data after {valid_up_to} bytes")
+            }
+            Self::LineTooLong { index } => write!(f, "line too long at index {index}"),
+            Self::GroupOverflow { index } => {
+                write!(f, "group at index {index} exceeds 32 bits")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Error of `DecoderReader`, locating the invalid input in the encoded stream
+///
+/// It is wrapped in the `io::ErrorKind::InvalidData` errors of the reader. Lines and columns
+/// start at 1, and count every character of the stream, skipped whitespaces included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionedError {
+    /// The decoding error itself
+    pub error: DecodeError,
+    /// Offset in the encoded stream of the byte that made decoding fail
+    pub offset: u64,
+    /// Line of that byte
+    pub line: u64,
+    /// Column of that byte in its line
+    pub column: u64,
+}
+
+impl fmt::Display for PositionedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {} (offset {})",
+            self.error, self.line, self.column, self.offset
+        )
+    }
+}
+
+impl core::error::Error for PositionedError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Error returned by `decode_salvage()`, carrying the bytes decoded before the failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SalvageError {
+    /// The bytes of the complete groups decoded before the failure
+    pub decoded: Vec<u8>,
+    /// Index in the input of the byte that made decoding fail
+    pub position: usize,
+    /// The decoding error itself
+    pub error: DecodeError,
+}
+
+impl fmt::Display for SalvageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, after {} decoded bytes",
+            self.error,
+            self.decoded.len()
+        )
+    }
+}
+
+impl core::error::Error for SalvageError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Error of a conversion aborted through its `CancellationToken`
+///
+/// It is wrapped in an `io::ErrorKind::Other` error, telling cancellations apart from failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "operation cancelled")
+    }
+}
+
+impl core::error::Error for Cancelled {}
@@ -0,0 +1,278 @@
+//! Encoding and decoding of whole files
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::num::NonZeroU64;
+use std::path::Path;
+
+use crate::progress::{Progress, ProgressReader};
+use crate::transcode::{base85_to_bytes, bytes_to_base85};
+use crate::CancellationToken;
+
+/// `encode_file()` encodes the content of the `input` file into the `output` file
+///
+/// The file is streamed, and the number of bytes read from `input` is returned. `output` is
+/// created, or truncated if it exists.
+///
+/// # Errors
+///
+/// Returns any I/O error from opening, reading or writing the files.
+///
+/// # Example
+///
+/// ```no_run
+/// let len = base85rs::encode_file("secret.bin", "secret.b85").unwrap();
+/// ```
+pub fn encode_file(input: impl AsRef<Path>, output: impl AsRef<Path>) -> io::Result<u64> {
+    bytes_to_base85(File::open(input)?, File::create(output)?)
+}
+
+/// `decode_file()` decodes the content of the `input` file into the `output` file
+///
+/// The file is streamed, and the number of bytes written to `output` is returned. `output` is
+/// created, or truncated if it exists.
+///
+/// # Errors
+///
+/// Returns any I/O error from opening, reading or writing the files, and an
+/// `io::ErrorKind::InvalidData` error wrapping a `DecodeError` if `input` isn't valid base85.
+///
+/// # Example
+///
+/// ```no_run
+/// let len = base85rs::decode_file("secret.b85", "secret.bin").unwrap();
+/// ```
+pub fn decode_file(input: impl AsRef<Path>, output: impl AsRef<Path>) -> io::Result<u64> {
+    base85_to_bytes(File::open(input)?, File::create(output)?)
+}
+
+// Callback reporting the progress of a file conversion
+type ProgressCallback<'a> = Box<dyn FnMut(Progress) + 'a>;
+
+/// `FileOptions` configures the conversions of `encode_file_with()` / `decode_file_with()`
+///
+/// `FileOptions::default()` converts the file as `encode_file()` / `decode_file()` do.
+#[derive(Default)]
+pub struct FileOptions<'a> {
+    progress: Option<(NonZeroU64, ProgressCallback<'a>)>,
+    cancel: Option<CancellationToken>,
+}
+
+impl<'a> FileOptions<'a> {
+    /// `new()` creates options converting the file as is
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `with_progress()` calls `callback` every `interval` bytes read from the input file
+    ///
+    /// The reported total is the size of the input file, the callback is called a last time at its
+    /// end. When decoding, the progress counts encoded characters.
+    #[must_use]
+    pub fn with_progress(
+        mut self,
+        interval: NonZeroU64,
+        callback: impl FnMut(Progress) + 'a,
+    ) -> Self {
+        self.progress = Some((interval, Box::new(callback)));
+        self
+    }
+
+    /// `with_cancellation()` stops the conversion once `token` is cancelled
+    ///
+    /// `token` is checked between chunks of the input file, the output file keeps what was
+    /// converted before, and the conversion fails with an error wrapping `Cancelled`.
+    #[must_use]
+    pub fn with_cancellation(mut self, token: &CancellationToken) -> Self {
+        self.cancel = Some(token.clone());
+        self
+    }
+
+    // Open `input`, wrapped as the options require
+    fn open(self, input: &Path) -> io::Result<Box<dyn Read + 'a>> {
+        let file = File::open(input)?;
+        let reader: Box<dyn Read + 'a> = match self.progress {
+            Some((interval, callback)) => {
+                let total = file.metadata()?.len();
+                Box::new(ProgressReader::new(file, interval, callback).with_total(total))
+            }
+            None => Box::new(file),
+        };
+        Ok(match self.cancel {
+            Some(token) => Box::new(token.reader(reader)),
+            None => reader,
+        })
+    }
+}
+
+impl fmt::Debug for FileOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileOptions")
+            .field(
+                "progress_interval",
+                &self.progress.as_ref().map(|(interval, _)| interval),
+            )
+            .field("cancel", &self.cancel)
+            .finish_non_exhaustive()
+    }
+}
+
+/// `encode_file_with()` is `encode_file()`, with the progress reporting and cancellation set in
+/// `options`
+///
+/// # Errors
+///
+/// Returns any I/O error from opening, reading or writing the files, and an error wrapping
+/// `Cancelled` if the token of `options` is cancelled before the end.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::num::NonZeroU64;
+///
+/// let interval = NonZeroU64::new(64 << 20).unwrap();
+/// let options = base85rs::FileOptions::new().with_progress(interval, |progress| {
+///     eprintln!("{} / {:?} bytes", progress.processed, progress.total);
+/// });
+/// base85rs::encode_file_with("disk.img", "disk.b85", options).unwrap();
+/// ```
+pub fn encode_file_with(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: FileOptions<'_>,
+) -> io::Result<u64> {
+    bytes_to_base85(options.open(input.as_ref())?, File::create(output)?)
+}
+
+/// `decode_file_with()` is `decode_file()`, with the progress reporting and cancellation set in
+/// `options`
+///
+/// # Errors
+///
+/// Returns any I/O error from opening, reading or writing the files, an
+/// `io::ErrorKind::InvalidData` error wrapping a `DecodeError` if `input` isn't valid base85, and
+/// an error wrapping `Cancelled` if the token of `options` is cancelled before the end.
+///
+/// # Example
+///
+/// ```no_run
+/// let token = base85rs::CancellationToken::new();
+/// let handle = token.clone();
+/// std::thread::spawn(move || handle.cancel());
+/// let options = base85rs::FileOptions::new().with_cancellation(&token);
+/// match base85rs::decode_file_with("disk.b85", "disk.img", options) {
+///     Err(err) if base85rs::CancellationToken::is_cancellation(&err) => eprintln!("cancelled"),
+///     result => println!("{} bytes", result.unwrap()),
+/// }
+/// ```
+pub fn decode_file_with(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: FileOptions<'_>,
+) -> io::Result<u64> {
+    base85_to_bytes(options.open(input.as_ref())?, File::create(output)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn file_roundtrip() {
+        let dir = std::env::temp_dir();
+        let name = format!("base85rs-file-{}", std::process::id());
+        let (data, encoded, decoded) = (
+            dir.join(format!("{name}.bin")),
+            dir.join(format!("{name}.b85")),
+            dir.join(format!("{name}.out")),
+        );
+
+        let content: Vec<u8> = (0..100_000_u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&data, &content).unwrap();
+        assert_eq!(encode_file(&data, &encoded).unwrap(), 100_000);
+        assert_eq!(decode_file(&encoded, &decoded).unwrap(), 100_000);
+        assert_eq!(fs::read(&decoded).unwrap(), content);
+
+        for path in [data, encoded, decoded] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn file_progress() {
+        let dir = std::env::temp_dir();
+        let name = format!("base85rs-progress-{}", std::process::id());
+        let (data, encoded, decoded) = (
+            dir.join(format!("{name}.bin")),
+            dir.join(format!("{name}.b85")),
+            dir.join(format!("{name}.out")),
+        );
+        fs::write(&data, vec![7; 100_000]).unwrap();
+
+        let interval = NonZeroU64::new(30_000).unwrap();
+        let mut reports = Vec::new();
+        let options = FileOptions::new().with_progress(interval, |progress| reports.push(progress));
+        encode_file_with(&data, &encoded, options).unwrap();
+        let last = reports.last().copied();
+        assert_eq!(
+            last,
+            Some(Progress {
+                processed: 100_000,
+                total: Some(100_000)
+            })
+        );
+        assert_eq!(reports.len(), 4);
+
+        reports.clear();
+        let options = FileOptions::new().with_progress(interval, |progress| reports.push(progress));
+        decode_file_with(&encoded, &decoded, options).unwrap();
+        assert!(reports
+            .iter()
+            .all(|progress| progress.total == Some(125_000)));
+        assert_eq!(
+            reports.last().map(|progress| progress.processed),
+            Some(125_000)
+        );
+        assert_eq!(fs::read(&decoded).unwrap(), vec![7; 100_000]);
+
+        for path in [data, encoded, decoded] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn file_cancelled() {
+        let dir = std::env::temp_dir();
+        let name = format!("base85rs-cancel-{}", std::process::id());
+        let (data, encoded) = (
+            dir.join(format!("{name}.bin")),
+            dir.join(format!("{name}.b85")),
+        );
+        fs::write(&data, [0; 100]).unwrap();
+
+        let token = CancellationToken::new();
+        let options = FileOptions::new().with_cancellation(&token);
+        assert_eq!(encode_file_with(&data, &encoded, options).unwrap(), 100);
+        token.cancel();
+        let mut reports = 0;
+        let options = FileOptions::new()
+            .with_progress(NonZeroU64::MIN, |_| reports += 1)
+            .with_cancellation(&token);
+        let err = decode_file_with(&encoded, &data, options).unwrap_err();
+        assert!(CancellationToken::is_cancellation(&err));
+        assert_eq!(reports, 0);
+
+        for path in [data, encoded] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn file_missing() {
+        let err = encode_file("/nonexistent/input", "/nonexistent/output").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
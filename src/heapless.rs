@@ -0,0 +1,69 @@
+//! Encoding and decoding into fixed capacity `heapless` containers
+
+use crate::{ascii_str, decode_with_sink, encode_slice, encoded_len, DecodeError, EncodeError};
+
+/// `encode_heapless()` turns a slice of bytes into a base85 encoded `heapless::String`
+///
+/// # Errors
+///
+/// Returns `EncodeError::OutputTooSmall` if the encoded data doesn't fit in `N` characters.
+///
+/// # Example
+///
+/// ```
+/// let encoded = base85rs::encode_heapless::<8>(b"aaaaa").unwrap();
+/// assert_eq!(encoded, "VPRomVE");
+/// ```
+pub fn encode_heapless<const N: usize>(data: &[u8]) -> Result<::heapless::String<N>, EncodeError> {
+    let mut out = ::heapless::Vec::<u8, N>::new();
+    out.resize_default(encoded_len(data.len()))
+        .map_err(|_| EncodeError::OutputTooSmall)?;
+    encode_slice(data, &mut out)?;
+    ::heapless::String::try_from(ascii_str(&out)).map_err(|_| EncodeError::OutputTooSmall)
+}
+
+/// `decode_heapless()` try to decode a base85 encoded &str into a `heapless::Vec`
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX` and `DecodeError::OutputTooSmall`
+/// if the decoded data doesn't fit in `N` bytes.
+///
+/// # Example
+///
+/// ```
+/// let decoded = base85rs::decode_heapless::<8>("VPRomVE").unwrap();
+/// assert_eq!(decoded, b"aaaaa");
+/// ```
+pub fn decode_heapless<const N: usize>(instr: &str) -> Result<::heapless::Vec<u8, N>, DecodeError> {
+    let mut out = ::heapless::Vec::new();
+    decode_with_sink(instr.as_bytes(), |bytes| {
+        out.extend_from_slice(bytes)
+            .map_err(|_| DecodeError::OutputTooSmall)
+    })?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_capacity() {
+        assert_eq!(encode_heapless::<7>(b"aaaaa").unwrap(), "VPRomVE");
+        assert_eq!(
+            encode_heapless::<6>(b"aaaaa"),
+            Err(EncodeError::OutputTooSmall)
+        );
+    }
+
+    #[test]
+    fn decode_capacity() {
+        assert_eq!(decode_heapless::<5>("VPRomVE").unwrap(), b"aaaaa");
+        assert_eq!(
+            decode_heapless::<4>("VPRomVE"),
+            Err(DecodeError::OutputTooSmall)
+        );
+    }
+}
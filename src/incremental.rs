@@ -0,0 +1,253 @@
+//! Incremental encoder and decoder, fed with arbitrary sized pieces of input
+
+use crate::{decode_digits, encode_u32_chunk, to_x85, DecodeError};
+
+/// `Encoder` encodes data fed in arbitrary sized pieces, keeping the trailing partial group
+/// between calls to `update()`
+///
+/// # Example
+///
+/// ```
+/// let mut encoded = Vec::new();
+/// let mut encoder = base85rs::core::Encoder::default();
+/// encoder.update(b"aa", |chars| encoded.extend_from_slice(chars));
+/// encoder.update(b"aaa", |chars| encoded.extend_from_slice(chars));
+/// encoder.finish(|chars| encoded.extend_from_slice(chars));
+/// assert_eq!(encoded, b"VPRomVE");
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "zeroize", derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop))]
+pub struct Encoder {
+    tail: [u8; 4],
+    filled: usize,
+}
+
+impl Encoder {
+    /// `update()` encodes `data`, handing every complete group of 5 characters over to `sink`
+    pub fn update(&mut self, data: &[u8], mut sink: impl FnMut(&[u8])) {
+        let mut buffer = [0; 5];
+        let mut data = data;
+
+        if self.filled != 0 {
+            let missing = (4 - self.filled).min(data.len());
+            for (byte, &chr) in self.tail.iter_mut().skip(self.filled).zip(data) {
+                *byte = chr;
+            }
+            self.filled += missing;
+            data = data.get(missing..).unwrap_or_default();
+            if self.filled < 4 {
+                return;
+            }
+            let _ = encode_u32_chunk(&self.tail, &mut buffer);
+            sink(&buffer);
+            self.filled = 0;
+        }
+
+        let mut chunks = data.chunks_exact(4);
+        for chunk in &mut chunks {
+            let _ = encode_u32_chunk(chunk, &mut buffer);
+            sink(&buffer);
+        }
+        let rem = chunks.remainder();
+        for (byte, &chr) in self.tail.iter_mut().zip(rem) {
+            *byte = chr;
+        }
+        self.filled = rem.len();
+    }
+
+    /// `finish()` encodes the trailing partial group, if any, handing its 2 to 4 characters
+    /// over to `sink`
+    pub fn finish(self, mut sink: impl FnMut(&[u8])) {
+        if self.filled != 0 {
+            let mut buffer = [0; 5];
+            let tail = self.tail.get(..self.filled).unwrap_or(&self.tail);
+            let c = encode_u32_chunk(tail, &mut buffer);
+            sink(buffer.get(..=c).unwrap_or(&buffer));
+        }
+    }
+}
+
+/// `Decoder` decodes text fed in arbitrary sized pieces, keeping the digits of the trailing
+/// partial group between calls to `update()`
+///
+/// Spaces are skipped. The sink receives every decoded group, and can stop decoding by returning
+/// an error, such as `DecodeError::OutputTooSmall` when its buffer is full.
+///
+/// # Example
+///
+/// ```
+/// let mut out = [0; 8];
+/// let mut written = 0;
+/// let mut sink = |bytes: &[u8]| {
+///     out[written..written + bytes.len()].copy_from_slice(bytes);
+///     written += bytes.len();
+///     Ok(())
+/// };
+/// let mut decoder = base85rs::core::Decoder::default();
+/// decoder.update(b"VPRo", &mut sink).unwrap();
+/// decoder.update(b"m VE", &mut sink).unwrap();
+/// decoder.finish(&mut sink).unwrap();
+/// assert_eq!(&out[..written], b"aaaaa");
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "zeroize", derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop))]
+pub struct Decoder {
+    digits: [u8; 5],
+    filled: usize,
+    // Index in the whole input of the next byte to decode, for error reporting
+    index: usize,
+    // Index in the whole input of the first digit of the current group
+    start: usize,
+}
+
+impl Decoder {
+    /// `set_index()` sets the index in the whole input of the next byte to decode, reported by
+    /// the errors, for inputs decoded piecewise
+    pub fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+
+    /// `index()` returns the index in the whole input of the next byte to decode
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    // Index in the whole input of the first digit of the current group, or of the last one
+    pub(crate) fn group_start(&self) -> usize {
+        self.start
+    }
+
+    /// `update()` decodes `instr`, skipping spaces, and hands every decoded group of 4 bytes over
+    /// to `sink`
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::InvalidByte` if `instr` contains a character outside of the
+    /// alphabet, `DecodeError::GroupOverflow` if a group exceeds `u32::MAX`, and any error of
+    /// `sink`.
+    #[inline]
+    pub fn update(
+        &mut self,
+        instr: &[u8],
+        mut sink: impl FnMut(&[u8]) -> Result<(), DecodeError>,
+    ) -> Result<(), DecodeError> {
+        for &chr in instr {
+            let index = self.index;
+            self.index += 1;
+            if chr == 0x20 {
+                continue;
+            }
+            let digit = to_x85(chr).ok_or(DecodeError::InvalidByte { index, byte: chr })?;
+            if self.filled == 0 {
+                self.start = index;
+            }
+            #[cfg(feature = "unsafe-fast")]
+            {
+                debug_assert!(self.filled < self.digits.len());
+                // SAFETY: `filled` is reset as soon as it reaches 5
+                unsafe { *self.digits.get_unchecked_mut(self.filled) = digit };
+            }
+            #[cfg(not(feature = "unsafe-fast"))]
+            if let Some(slot) = self.digits.get_mut(self.filled) {
+                // Always the case, `filled` is reset as soon as it reaches 5
+                *slot = digit;
+            }
+            self.filled += 1;
+            if self.filled == 5 {
+                sink(&decode_digits(self.digits, self.start)?)?;
+                self.filled = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// `finish()` decodes the trailing partial group, if any, and hands its 1 to 3 bytes over to
+    /// `sink`
+    ///
+    /// The group is completed with the highest digit before decoding, only the meaningful bytes
+    /// are then handed over.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::GroupOverflow` if the completed group exceeds `u32::MAX`, and any
+    /// error of `sink`.
+    #[inline]
+    pub fn finish(
+        mut self,
+        mut sink: impl FnMut(&[u8]) -> Result<(), DecodeError>,
+    ) -> Result<(), DecodeError> {
+        if self.filled > 1 {
+            self.digits
+                .iter_mut()
+                .skip(self.filled)
+                .for_each(|digit| *digit = 84);
+            let bytes = decode_digits(self.digits, self.start)?;
+            sink(bytes.get(..self.filled - 1).unwrap_or(&bytes))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn encoder_split_input() {
+        let data = "countercompetition".as_bytes();
+        for split in 0..data.len() {
+            let mut outdata = Vec::<u8>::new();
+            let mut encoder = Encoder::default();
+            encoder.update(&data[..split], |chars| outdata.extend(chars));
+            encoder.update(&data[split..], |chars| outdata.extend(chars));
+            encoder.finish(|chars| outdata.extend(chars));
+            assert_eq!(outdata, encode(data).as_bytes());
+        }
+    }
+
+    #[test]
+    fn decoder_split_input() {
+        let instr = "V{dhCbY *g5Z*6d8bZK ;HZ*B".as_bytes();
+        for split in 0..instr.len() {
+            let mut outdata = Vec::<u8>::new();
+            let mut decoder = Decoder::default();
+            let mut sink = |bytes: &[u8]| {
+                outdata.extend(bytes);
+                Ok(())
+            };
+            decoder.update(&instr[..split], &mut sink).unwrap();
+            decoder.update(&instr[split..], &mut sink).unwrap();
+            decoder.finish(&mut sink).unwrap();
+            assert_eq!(outdata, "countercompetition".as_bytes());
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn encoder_zeroized() {
+        fn zeroized_on_drop(_: &impl ::zeroize::ZeroizeOnDrop) {}
+        let mut encoder = Encoder::default();
+        encoder.update(b"aa", |_| {});
+        zeroized_on_drop(&encoder);
+        ::zeroize::Zeroize::zeroize(&mut encoder);
+        assert_eq!(encoder.tail, [0; 4]);
+        let mut outdata = Vec::<u8>::new();
+        encoder.finish(|chars| outdata.extend(chars));
+        assert!(outdata.is_empty());
+    }
+
+    #[test]
+    fn decoder_error_index() {
+        let mut decoder = Decoder::default();
+        decoder.update(b"VPR", |_| Ok(())).unwrap();
+        assert_eq!(
+            decoder.update(b"om]", |_| Ok(())),
+            Err(DecodeError::InvalidByte {
+                index: 5,
+                byte: b']'
+            })
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Inputs accepted by `decode()`
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Text or bytes that `decode()` accepts as its input
+///
+/// This trait is sealed, it is implemented for `str`, `[u8]`, `[u8; N]`, `String`, `Vec<u8>` and
+/// references to them.
+pub trait AsDecodeInput: sealed::Sealed {
+    /// The bytes of the encoded input
+    fn as_decode_input(&self) -> &[u8];
+}
+
+impl sealed::Sealed for str {}
+
+impl AsDecodeInput for str {
+    fn as_decode_input(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl sealed::Sealed for [u8] {}
+
+impl AsDecodeInput for [u8] {
+    fn as_decode_input(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<const N: usize> sealed::Sealed for [u8; N] {}
+
+impl<const N: usize> AsDecodeInput for [u8; N] {
+    fn as_decode_input(&self) -> &[u8] {
+        self
+    }
+}
+
+impl sealed::Sealed for String {}
+
+impl AsDecodeInput for String {
+    fn as_decode_input(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl sealed::Sealed for Vec<u8> {}
+
+impl AsDecodeInput for Vec<u8> {
+    fn as_decode_input(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<T: AsDecodeInput + ?Sized> sealed::Sealed for &T {}
+
+impl<T: AsDecodeInput + ?Sized> AsDecodeInput for &T {
+    fn as_decode_input(&self) -> &[u8] {
+        (**self).as_decode_input()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decode;
+
+    #[test]
+    #[allow(clippy::needless_borrows_for_generic_args)]
+    fn decode_inputs() {
+        let expected = Some(b"aaaaa".to_vec());
+        assert_eq!(decode("VPRomVE"), expected);
+        assert_eq!(decode(b"VPRomVE"), expected);
+        assert_eq!(decode(b"VPRomVE".as_slice()), expected);
+        assert_eq!(decode(String::from("VPRomVE")), expected);
+        assert_eq!(decode(&String::from("VPRomVE")), expected);
+        assert_eq!(decode(b"VPRomVE".to_vec()), expected);
+        assert_eq!(decode(&&"VPRomVE"), expected);
+    }
+}
@@ -0,0 +1,303 @@
+//! Radix-85 rendering of integers, as RFC1924 defines it for 128-bit numbers, and of smaller
+//! integers with the minimal number of digits
+
+use crate::{to_x85, DecodeError, EncodeError, RFC1924_ALPHABET};
+
+// Number of digits needed to render any `u32`, `u64` and `u128`
+const U32_DIGITS: usize = 5;
+const U64_DIGITS: usize = 10;
+const U128_DIGITS: usize = 20;
+
+// Render `value` in radix 85, left padded with zeros up to `width` digits.
+fn encode_radix(value: u128, width: usize) -> String {
+    let mut value = value;
+    let mut outdata = [b'0'; U128_DIGITS];
+    let mut start = U128_DIGITS;
+    while value != 0 || U128_DIGITS - start < width {
+        start -= 1;
+        outdata[start] =
+            RFC1924_ALPHABET[usize::from(u8::try_from(value % 85).unwrap_or_default())];
+        value /= 85;
+    }
+    outdata[start..]
+        .iter()
+        .map(|&chr| char::from(chr))
+        .collect()
+}
+
+// Parse a radix 85 number of at most `max_digits` digits.
+fn decode_radix(instr: &str, max_digits: usize) -> Result<u128, DecodeError> {
+    if instr.is_empty() || instr.len() > max_digits {
+        return Err(DecodeError::InvalidLength);
+    }
+
+    instr
+        .bytes()
+        .enumerate()
+        .try_fold(0_u128, |acc, (index, chr)| {
+            let value = to_x85(chr).ok_or(DecodeError::InvalidByte { index, byte: chr })?;
+            acc.checked_mul(85)
+                .and_then(|acc| acc.checked_add(u128::from(value)))
+                .ok_or(DecodeError::Overflow)
+        })
+}
+
+/// `encode_u128()` turns a `u128` into its 20 characters RFC1924 representation
+///
+/// Unlike `encode()`, the value is rendered as a single number and leading zeros are kept.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::encode_u128(0), "00000000000000000000");
+/// assert_eq!(base85rs::encode_u128(u128::MAX), "=r54lj&NUUO~Hi%c2ym0");
+/// ```
+#[must_use]
+pub fn encode_u128(value: u128) -> String {
+    encode_radix(value, U128_DIGITS)
+}
+
+/// `decode_u128()` try to decode a RFC1924 representation of a `u128`
+///
+/// Leading zeros may be omitted, so any input from 1 to 20 characters is accepted.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidLength` for an empty input or an input longer than 20 characters,
+/// `DecodeError::InvalidByte` if it contains a character outside of the alphabet and
+/// `DecodeError::Overflow` if the value doesn't fit in a `u128`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::decode_u128("000000000000000000VE"), Ok(2_649));
+/// assert_eq!(base85rs::decode_u128("VE"), Ok(2_649));
+/// ```
+pub fn decode_u128(instr: &str) -> Result<u128, DecodeError> {
+    decode_radix(instr, U128_DIGITS)
+}
+
+/// `encode_rfc1924_128()` turns exactly 16 bytes into their 20 characters RFC1924 representation
+///
+/// This is the actual scope of the RFC: the bytes are read as a single big-endian 128-bit number,
+/// as `encode_u128()` does, unlike `encode()` which splits them in groups of 4 bytes.
+///
+/// # Errors
+///
+/// Returns `EncodeError::InvalidLength` if `data` isn't exactly 16 bytes long.
+///
+/// # Example
+///
+/// ```
+/// let addr: std::net::Ipv6Addr = "1080:0:0:0:8:800:200C:417A".parse().unwrap();
+/// assert_eq!(base85rs::encode_rfc1924_128(&addr.octets()).unwrap(), "4)+k&C#VzJ4br>0wv%Yp");
+/// assert!(base85rs::encode_rfc1924_128(b"aaaaa").is_err());
+/// ```
+pub fn encode_rfc1924_128(data: &[u8]) -> Result<String, EncodeError> {
+    let bytes: [u8; 16] = data.try_into().map_err(|_| EncodeError::InvalidLength)?;
+    Ok(encode_u128(u128::from_be_bytes(bytes)))
+}
+
+/// `decode_rfc1924_128()` try to decode exactly 20 characters of RFC1924 representation to 16 bytes
+///
+/// Unlike `decode_u128()`, leading zeros can't be omitted, and unlike `decode()`, whitespaces
+/// aren't accepted.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidLength` if the input isn't exactly 20 characters long,
+/// `DecodeError::InvalidByte` if it contains a character outside of the alphabet and
+/// `DecodeError::Overflow` if the value doesn't fit in 128 bits.
+///
+/// # Example
+///
+/// ```
+/// let bytes = base85rs::decode_rfc1924_128("4)+k&C#VzJ4br>0wv%Yp").unwrap();
+/// assert_eq!(bytes[..2], [0x10, 0x80]);
+/// assert!(base85rs::decode_rfc1924_128("VE").is_err());
+/// ```
+pub fn decode_rfc1924_128(instr: &str) -> Result<[u8; 16], DecodeError> {
+    if instr.len() != 20 {
+        return Err(DecodeError::InvalidLength);
+    }
+    decode_u128(instr).map(u128::to_be_bytes)
+}
+
+/// `encode_u32()` turns a `u32` into the minimal number of base85 digits
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::encode_u32(0), "0");
+/// assert_eq!(base85rs::encode_u32(2_649), "VE");
+/// ```
+#[must_use]
+pub fn encode_u32(value: u32) -> String {
+    encode_radix(u128::from(value), 1)
+}
+
+/// `decode_u32()` try to decode base85 digits into a `u32`
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidLength` for an empty input or an input longer than 5 characters,
+/// `DecodeError::InvalidByte` if it contains a character outside of the alphabet and
+/// `DecodeError::Overflow` if the value doesn't fit in a `u32`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::decode_u32("VE"), Ok(2_649));
+/// ```
+pub fn decode_u32(instr: &str) -> Result<u32, DecodeError> {
+    u32::try_from(decode_radix(instr, U32_DIGITS)?).map_err(|_| DecodeError::Overflow)
+}
+
+/// `encode_u64()` turns a `u64` into the minimal number of base85 digits
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::encode_u64(0), "0");
+/// assert_eq!(base85rs::encode_u64(u64::MAX), "_sw2=@*|O0");
+/// ```
+#[must_use]
+pub fn encode_u64(value: u64) -> String {
+    encode_radix(u128::from(value), 1)
+}
+
+/// `decode_u64()` try to decode base85 digits into a `u64`
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidLength` for an empty input or an input longer than 10 characters,
+/// `DecodeError::InvalidByte` if it contains a character outside of the alphabet and
+/// `DecodeError::Overflow` if the value doesn't fit in a `u64`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::decode_u64("_sw2=@*|O0"), Ok(u64::MAX));
+/// ```
+pub fn decode_u64(instr: &str) -> Result<u64, DecodeError> {
+    u64::try_from(decode_radix(instr, U64_DIGITS)?).map_err(|_| DecodeError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_u128_rfc_example() {
+        // 1080:0:0:0:8:800:200C:417A, from RFC1924 section 5
+        assert_eq!(
+            encode_u128(0x1080_0000_0000_0000_0008_0800_200C_417A),
+            "4)+k&C#VzJ4br>0wv%Yp"
+        );
+    }
+
+    #[test]
+    fn decode_u128_rfc_example() {
+        assert_eq!(
+            decode_u128("4)+k&C#VzJ4br>0wv%Yp"),
+            Ok(0x1080_0000_0000_0000_0008_0800_200C_417A)
+        );
+    }
+
+    #[test]
+    fn u128_roundtrip() {
+        for value in [0, 1, 84, 85, u128::from(u64::MAX), u128::MAX - 1, u128::MAX] {
+            assert_eq!(decode_u128(&encode_u128(value)), Ok(value));
+        }
+    }
+
+    #[test]
+    fn decode_u128_leading_zeros() {
+        assert_eq!(decode_u128("0"), Ok(0));
+        assert_eq!(decode_u128("00000000000000000000"), Ok(0));
+        assert_eq!(
+            decode_u128("000000000000000000010"),
+            Err(DecodeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn decode_u128_overflow() {
+        // u128::MAX + 1
+        assert_eq!(
+            decode_u128("=r54lj&NUUO~Hi%c2ym1"),
+            Err(DecodeError::Overflow)
+        );
+        assert_eq!(
+            decode_u128("~~~~~~~~~~~~~~~~~~~~"),
+            Err(DecodeError::Overflow)
+        );
+    }
+
+    #[test]
+    fn decode_u128_invalid() {
+        assert_eq!(decode_u128(""), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            decode_u128("VE]"),
+            Err(DecodeError::InvalidByte {
+                index: 2,
+                byte: b']'
+            })
+        );
+    }
+
+    #[test]
+    fn encode_minimal_digits() {
+        assert_eq!(encode_u32(84), "~");
+        assert_eq!(encode_u32(85), "10");
+        assert_eq!(encode_u32(u32::MAX), "|NsC0");
+        assert_eq!(encode_u64(u64::from(u32::MAX)), "|NsC0");
+    }
+
+    #[test]
+    fn u32_u64_roundtrip() {
+        for value in [0, 1, 84, 85, 2_649, u32::MAX - 1, u32::MAX] {
+            assert_eq!(decode_u32(&encode_u32(value)), Ok(value));
+        }
+        for value in [0, 1, 84, 85, u64::from(u32::MAX) + 1, u64::MAX] {
+            assert_eq!(decode_u64(&encode_u64(value)), Ok(value));
+        }
+    }
+
+    #[test]
+    fn decode_u32_u64_invalid() {
+        assert_eq!(decode_u32("|NsC1"), Err(DecodeError::Overflow));
+        assert_eq!(decode_u32("000000"), Err(DecodeError::InvalidLength));
+        assert_eq!(decode_u64("_sw2=@*|O1"), Err(DecodeError::Overflow));
+        assert_eq!(decode_u64(""), Err(DecodeError::InvalidLength));
+    }
+
+    #[test]
+    fn strict_lengths() {
+        assert_eq!(
+            encode_rfc1924_128(&[0; 15]),
+            Err(EncodeError::InvalidLength)
+        );
+        assert_eq!(
+            encode_rfc1924_128(&[0; 17]),
+            Err(EncodeError::InvalidLength)
+        );
+        assert_eq!(
+            encode_rfc1924_128(&[0xFF; 16]).unwrap(),
+            "=r54lj&NUUO~Hi%c2ym0"
+        );
+
+        assert_eq!(decode_rfc1924_128("=r54lj&NUUO~Hi%c2ym0"), Ok([0xFF; 16]));
+        assert_eq!(
+            decode_rfc1924_128("r54lj&NUUO~Hi%c2ym0"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            decode_rfc1924_128("=r54lj&NUUO~Hi%c2ym "),
+            Err(DecodeError::InvalidByte {
+                index: 19,
+                byte: b' '
+            })
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Compact representation of IPv6 addresses, the purpose RFC1924 was written for
+
+use std::net::Ipv6Addr;
+
+use crate::{decode_rfc1924_128, encode_rfc1924_128, DecodeError};
+
+/// `encode_ipv6()` turns an IPv6 address into its 20 characters RFC1924 representation
+///
+/// # Example
+///
+/// ```
+/// use std::net::Ipv6Addr;
+///
+/// let addr: Ipv6Addr = "1080:0:0:0:8:800:200C:417A".parse().unwrap();
+/// assert_eq!(base85rs::encode_ipv6(addr), "4)+k&C#VzJ4br>0wv%Yp");
+/// ```
+#[must_use]
+pub fn encode_ipv6(addr: Ipv6Addr) -> String {
+    // Can't fail, an address is 16 bytes long
+    encode_rfc1924_128(&addr.octets()).unwrap_or_default()
+}
+
+/// `decode_ipv6()` try to decode a RFC1924 representation of an IPv6 address
+///
+/// # Errors
+///
+/// Returns the same errors as `decode_rfc1924_128()`.
+///
+/// # Example
+///
+/// ```
+/// use std::net::Ipv6Addr;
+///
+/// let addr = base85rs::decode_ipv6("4)+k&C#VzJ4br>0wv%Yp").unwrap();
+/// assert_eq!(addr, "1080:0:0:0:8:800:200C:417A".parse::<Ipv6Addr>().unwrap());
+/// ```
+pub fn decode_ipv6(instr: &str) -> Result<Ipv6Addr, DecodeError> {
+    decode_rfc1924_128(instr).map(Ipv6Addr::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv6_roundtrip() {
+        for addr in [
+            Ipv6Addr::UNSPECIFIED,
+            Ipv6Addr::LOCALHOST,
+            Ipv6Addr::from(u128::MAX),
+        ] {
+            let encoded = encode_ipv6(addr);
+            assert_eq!(encoded.len(), 20);
+            assert_eq!(decode_ipv6(&encoded), Ok(addr));
+        }
+    }
+
+    #[test]
+    fn decode_ipv6_invalid() {
+        assert_eq!(decode_ipv6(""), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            decode_ipv6("~~~~~~~~~~~~~~~~~~~~"),
+            Err(DecodeError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Lazy encoding and decoding, producing the output as an iterator
+
+use crate::{encode_u32_chunk, incremental, DecodeError};
+
+/// `encode_chars()` lazily encodes a slice of bytes, yielding base85 characters
+///
+/// # Example
+///
+/// ```
+/// let quoted: String = std::iter::once('"')
+///     .chain(base85rs::encode_chars(b"aaaaa"))
+///     .chain(std::iter::once('"'))
+///     .collect();
+/// assert_eq!(quoted, "\"VPRomVE\"");
+/// ```
+pub fn encode_chars(data: &[u8]) -> impl Iterator<Item = char> + '_ {
+    data.chunks(4).flat_map(|chunk| {
+        let mut buffer = [0; 5];
+        let len = encode_u32_chunk(chunk, &mut buffer);
+        buffer.into_iter().take(len + 1).map(char::from)
+    })
+}
+
+/// `encode_wrapped_iter()` lazily encodes a slice of bytes, yielding segments of `width`
+/// characters, the last one possibly shorter
+///
+/// Each segment is encoded when requested, so a payload can be sent as MQTT messages, SMS
+/// segments or QR frames without building the whole encoded string first. A `width` of 0 yields
+/// the encoded string as a single segment.
+///
+/// # Example
+///
+/// ```
+/// let segments: Vec<String> = base85rs::encode_wrapped_iter(b"aaaaa", 3).collect();
+/// assert_eq!(segments, ["VPR", "omV", "E"]);
+/// ```
+pub fn encode_wrapped_iter(data: &[u8], width: usize) -> impl Iterator<Item = String> + '_ {
+    let width = if width == 0 { usize::MAX } else { width };
+    let mut chars = encode_chars(data).peekable();
+    std::iter::from_fn(move || {
+        chars.peek()?;
+        Some(chars.by_ref().take(width).collect())
+    })
+}
+
+/// `decode_bytes_iter()` lazily decodes a base85 encoded &str, yielding decoded bytes
+///
+/// The iteration ends after the first error.
+///
+/// # Errors
+///
+/// Yields `DecodeError::InvalidByte` when reaching a character outside of the alphabet.
+///
+/// # Example
+///
+/// ```
+/// let decoded: Result<Vec<u8>, _> = base85rs::decode_bytes_iter("VPRomVE").collect();
+/// assert_eq!(decoded.unwrap(), b"aaaaa");
+///
+/// let mut bytes = base85rs::decode_bytes_iter("VPRomV]");
+/// assert_eq!(bytes.by_ref().take(4).count(), 4);
+/// assert!(bytes.next().unwrap().is_err());
+/// assert!(bytes.next().is_none());
+/// ```
+pub fn decode_bytes_iter(instr: &str) -> impl Iterator<Item = Result<u8, DecodeError>> + '_ {
+    let mut chars = instr.bytes();
+    let mut decoder = Some(incremental::Decoder::default());
+    // Bytes of the last decoded group, and the position of the next one to yield
+    let mut group = [0; 4];
+    let mut len = 0;
+    let mut pos = 0;
+
+    std::iter::from_fn(move || loop {
+        if pos < len {
+            pos += 1;
+            return Some(Ok(group[pos - 1]));
+        }
+        let sink = |bytes: &[u8]| {
+            group[..bytes.len()].copy_from_slice(bytes);
+            len = bytes.len();
+            pos = 0;
+            Ok(())
+        };
+        let result = match chars.next() {
+            Some(chr) => decoder.as_mut()?.update(&[chr], sink),
+            None => decoder.take()?.finish(sink),
+        };
+        if let Err(err) = result {
+            decoder = None;
+            return Some(Err(err));
+        }
+    })
+}
+
+/// `decode_groups()` lazily decodes a base85 encoded &str, yielding 4 bytes words
+///
+/// Whitespaces are skipped, and each group of 5 characters yields its 4 decoded bytes, for binary
+/// parsers working on fixed-size words without collecting the decoded bytes first. The iteration
+/// ends after the first error.
+///
+/// # Errors
+///
+/// Yields `DecodeError::InvalidByte` when reaching a character outside of the alphabet, and
+/// `DecodeError::GroupOverflow` for a group exceeding `u32::MAX`. A trailing partial group of 2
+/// to 4 characters decodes to less than a whole word: it yields `DecodeError::InvalidLength`
+/// instead, the input then not being a whole number of words.
+///
+/// # Example
+///
+/// ```
+/// let words: Vec<u32> = base85rs::decode_groups("VPRom VPRom")
+///     .map(|group| group.map(u32::from_be_bytes))
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(words, [0x6161_6161; 2]);
+///
+/// let mut groups = base85rs::decode_groups("VPRomVE");
+/// assert_eq!(groups.next(), Some(Ok(*b"aaaa")));
+/// assert_eq!(groups.next(), Some(Err(base85rs::DecodeError::InvalidLength)));
+/// assert_eq!(groups.next(), None);
+/// ```
+pub fn decode_groups(instr: &str) -> impl Iterator<Item = Result<[u8; 4], DecodeError>> + '_ {
+    let mut chars = instr.bytes();
+    let mut decoder = Some(incremental::Decoder::default());
+
+    std::iter::from_fn(move || loop {
+        let mut group = None;
+        let sink = |bytes: &[u8]| {
+            group = Some(<[u8; 4]>::try_from(bytes).map_err(|_| DecodeError::InvalidLength));
+            Ok(())
+        };
+        let result = match chars.next() {
+            Some(chr) => decoder.as_mut()?.update(&[chr], sink),
+            None => decoder.take()?.finish(sink),
+        };
+        if let Some(item) = result.and_then(|()| group.transpose()).transpose() {
+            if item.is_err() {
+                decoder = None;
+            }
+            return Some(item);
+        }
+    })
+}
+
+/// `decode_iter()` decodes the base85 characters of any iterator of bytes, skipping whitespaces
+///
+/// The characters don't need to be collected into a contiguous buffer first.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if a character is outside of the alphabet, its index being
+/// its position in the iterator.
+///
+/// # Example
+///
+/// ```
+/// let quoted = b"\"VPRomVE\"";
+/// let decoded = base85rs::decode_iter(quoted.iter().copied().filter(|&chr| chr != b'"'));
+/// assert_eq!(decoded.unwrap(), b"aaaaa");
+/// ```
+pub fn decode_iter(instr: impl IntoIterator<Item = u8>) -> Result<Vec<u8>, DecodeError> {
+    let mut outdata = Vec::new();
+    let mut decoder = incremental::Decoder::default();
+    let mut sink = |bytes: &[u8]| {
+        outdata.extend(bytes);
+        Ok(())
+    };
+    // Characters are decoded by batches, rather than one call per character
+    let mut batch = [0; 64];
+    let mut filled = 0;
+    for chr in instr {
+        batch[filled] = chr;
+        filled += 1;
+        if filled == batch.len() {
+            decoder.update(&batch, &mut sink)?;
+            filled = 0;
+        }
+    }
+    decoder.update(&batch[..filled], &mut sink)?;
+    decoder.finish(sink)?;
+    Ok(outdata)
+}
+
+/// `decode_chars()` decodes the base85 characters of any iterator of chars, skipping whitespaces
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if a character is outside of the alphabet, its index being
+/// its position in the iterator. Non-ASCII characters are reported as their Latin-1 byte, or
+/// `0xFF` beyond it.
+///
+/// # Example
+///
+/// ```
+/// let decoded = base85rs::decode_chars("VPR-om-VE".chars().filter(|&chr| chr != '-'));
+/// assert_eq!(decoded.unwrap(), b"aaaaa");
+/// ```
+pub fn decode_chars(instr: impl IntoIterator<Item = char>) -> Result<Vec<u8>, DecodeError> {
+    decode_iter(
+        instr
+            .into_iter()
+            .map(|chr| u8::try_from(chr).unwrap_or(0xFF)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn encode_chars_word_set() {
+        for data in ["", "a", "aaaa", "aaaaa", "countercompetition"] {
+            let encoded: String = encode_chars(data.as_bytes()).collect();
+            assert_eq!(encoded, encode(data.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn encode_wrapped_iter_widths() {
+        let data = "countercompetition".as_bytes();
+        let encoded = encode(data);
+        for width in 1..=encoded.len() + 1 {
+            let segments: Vec<String> = encode_wrapped_iter(data, width).collect();
+            assert!(segments.iter().all(|segment| segment.len() <= width));
+            assert_eq!(segments.len(), encoded.len().div_ceil(width));
+            assert_eq!(segments.concat(), encoded);
+        }
+        assert_eq!(encode_wrapped_iter(data, 0).collect::<Vec<_>>(), [encoded]);
+        assert_eq!(encode_wrapped_iter(b"", 5).count(), 0);
+    }
+
+    #[test]
+    fn decode_bytes_iter_word_set() {
+        for data in ["", "a", "aaaa", "aaaaa", "countercompetition"] {
+            let encoded = encode(data.as_bytes());
+            let decoded: Result<Vec<u8>, _> = decode_bytes_iter(&encoded).collect();
+            assert_eq!(decoded.unwrap(), data.as_bytes());
+        }
+    }
+
+    #[test]
+    fn decode_groups_words() {
+        let data: Vec<u8> = (0..40).collect();
+        let spaced: String = encode(&data).chars().flat_map(|chr| [chr, ' ']).collect();
+        let words: Vec<[u8; 4]> = decode_groups(&spaced).collect::<Result<_, _>>().unwrap();
+        assert_eq!(words.concat(), data);
+        assert_eq!(decode_groups("").count(), 0);
+        // A single trailing character decodes to nothing, as with `decode()`
+        assert_eq!(decode_groups("VPRomV").count(), 1);
+
+        let groups: Vec<_> = decode_groups("VPRom|NsC1VPRom").collect();
+        assert_eq!(
+            groups,
+            [Ok(*b"aaaa"), Err(DecodeError::GroupOverflow { index: 5 })]
+        );
+        let groups: Vec<_> = decode_groups("VP]omVPRom").collect();
+        assert_eq!(
+            groups,
+            [Err(DecodeError::InvalidByte {
+                index: 2,
+                byte: b']'
+            })]
+        );
+    }
+
+    #[test]
+    fn decode_iter_word_set() {
+        for data in ["", "a", "aaaa", "aaaaa", "countercompetition"] {
+            let spaced: String = encode(data.repeat(20).as_bytes())
+                .chars()
+                .flat_map(|chr| [chr, ' '])
+                .collect();
+            assert_eq!(
+                decode_iter(spaced.bytes()).unwrap(),
+                data.repeat(20).as_bytes()
+            );
+            assert_eq!(
+                decode_chars(spaced.chars()).unwrap(),
+                data.repeat(20).as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn decode_iter_error() {
+        let mut encoded = encode(&[0; 100]).into_bytes();
+        encoded[70] = b']';
+        assert_eq!(
+            decode_iter(encoded),
+            Err(DecodeError::InvalidByte {
+                index: 70,
+                byte: b']'
+            })
+        );
+        assert_eq!(
+            decode_chars("VPé".chars()),
+            Err(DecodeError::InvalidByte {
+                index: 2,
+                byte: 0xE9
+            })
+        );
+    }
+
+    #[test]
+    fn decode_bytes_iter_error() {
+        let decoded: Vec<_> = decode_bytes_iter("VE ]VE").collect();
+        assert_eq!(
+            decoded,
+            [Err(DecodeError::InvalidByte {
+                index: 3,
+                byte: b']'
+            })]
+        );
+    }
+}
@@ -4,65 +4,260 @@
 //! variant will most likely been seen in CTF challenges.
 //!
 //! During decoding, whitespaces are ignored.
+//!
+//! The crate forbids unsafe code, unless one of the `capi`, `python` or `unsafe-fast` features is
+//! enabled.
+
+#![cfg_attr(
+    not(any(feature = "capi", feature = "python", feature = "unsafe-fast")),
+    forbid(unsafe_code)
+)]
+
+// Emit a `tracing` event at `$level` with the `tracing` feature, nothing otherwise
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+    };
+}
+
+mod alphabet;
+pub mod armor;
+#[cfg(feature = "arrayvec")]
+mod arrayvec;
+#[cfg(feature = "ascii85")]
+pub mod ascii85;
+#[cfg(feature = "bignum")]
+pub mod bignum;
+mod cancel;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod checksum;
+mod codec;
+#[cfg(feature = "flate2")]
+mod compress;
+pub mod core;
+#[cfg(feature = "ct")]
+pub mod ct;
+#[cfg(feature = "data-encoding")]
+pub mod data_encoding;
+#[cfg(feature = "digest")]
+mod digest;
+mod error;
+mod file;
+#[cfg(feature = "heapless")]
+mod heapless;
+mod incremental;
+mod input;
+mod integer;
+mod ipv6;
+mod iter;
+#[cfg(test)]
+mod oracle;
+#[cfg(feature = "ordered")]
+pub mod ordered;
+mod partial;
+pub mod pipeline;
+mod pool;
+mod progress;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "python")]
+mod python;
+mod random_access;
+mod records;
+#[cfg(feature = "serde_with")]
+pub mod serde_with;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod simd;
+#[cfg(feature = "smallvec")]
+mod smallvec;
+mod stream;
+pub mod transcode;
+#[cfg(feature = "uuid")]
+mod uuid;
+mod validate;
+#[cfg(kani)]
+mod verification;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "z85")]
+pub mod z85;
+#[cfg(feature = "z85p")]
+pub mod z85p;
+#[cfg(feature = "zcert")]
+pub mod zcert;
+#[cfg(feature = "zeroize")]
+mod zeroize;
+
+pub use crate::alphabet::{decode_with_alphabet, encode_with_alphabet, Alphabet};
+#[cfg(feature = "arrayvec")]
+pub use crate::arrayvec::encode_array_string;
+pub use crate::cancel::{CancellableReader, CancellationToken};
+pub use crate::checksum::{decode_checked, encode_checked};
+pub use crate::codec::{
+    detect_variant, Base85Builder, ByteOrder, Codec, Padding, Variant, Whitespace,
+};
+#[cfg(feature = "flate2")]
+pub use crate::compress::{decode_decompressed, encode_compressed};
+#[cfg(feature = "digest")]
+pub use crate::digest::{DigestReader, DigestWriter};
+pub use crate::error::{
+    AlphabetError, Cancelled, DecodeError, EncodeError, PositionedError, SalvageError,
+};
+pub use crate::file::{decode_file, decode_file_with, encode_file, encode_file_with, FileOptions};
+#[cfg(feature = "heapless")]
+pub use crate::heapless::{decode_heapless, encode_heapless};
+pub use crate::input::AsDecodeInput;
+pub use crate::integer::{
+    decode_rfc1924_128, decode_u128, decode_u32, decode_u64, encode_rfc1924_128, encode_u128,
+    encode_u32, encode_u64,
+};
+pub use crate::ipv6::{decode_ipv6, encode_ipv6};
+pub use crate::iter::{
+    decode_bytes_iter, decode_chars, decode_groups, decode_iter, encode_chars, encode_wrapped_iter,
+};
+pub use crate::partial::{decode_partial, decode_salvage, PartialDecode, StopReason};
+pub use crate::pool::BufferPool;
+pub use crate::progress::{Progress, ProgressReader};
+pub use crate::random_access::RandomAccessDecoder;
+pub use crate::records::{decode_lines, decode_records};
+#[cfg(feature = "smallvec")]
+pub use crate::smallvec::{decode_small, SMALL_CAPACITY};
+pub use crate::stream::{copy_decode, copy_encode, encode_reader, DecoderReader, EncoderWriter};
+#[cfg(feature = "uuid")]
+pub use crate::uuid::{decode_uuid, encode_uuid};
+pub use crate::validate::{validate_reader, ValidationPolicy};
+#[cfg(feature = "zeroize")]
+pub use crate::zeroize::{decode_zeroizing, encode_zeroizing};
+
+// Marks bytes outside of the alphabet in a table built by `digits_table()`
+const INVALID_DIGIT: u8 = 0xFF;
+
+// Build the table giving the digit of every character of `alphabet`, `INVALID_DIGIT` for bytes
+// outside of it, so that encoding and decoding tables can't drift apart.
+const fn digits_table(alphabet: &[u8; 85]) -> [u8; 256] {
+    let mut digits = [INVALID_DIGIT; 256];
+    let mut i = 0;
+    while i < 85 {
+        #[allow(clippy::cast_possible_truncation)]
+        let digit = i as u8;
+        digits[alphabet[i] as usize] = digit;
+        i += 1;
+    }
+    digits
+}
+
+const RFC1924_DIGITS: [u8; 256] = digits_table(RFC1924_ALPHABET);
+
+// The alphabet, indexed by any byte so that looking a digit up needs no bounds check. Only the 85
+// first entries are meaningful.
+const RFC1924_CHARS: [u8; 256] = {
+    let mut chars = [0; 256];
+    let mut i = 0;
+    while i < 85 {
+        chars[i] = RFC1924_ALPHABET[i];
+        i += 1;
+    }
+    chars
+};
 
 #[inline]
 fn to_x85(c: u8) -> Option<u8> {
-    match c {
-        b'0'..=b'9' => Some(c - b'0'),
-        b'A'..=b'Z' => Some(c - b'A' + 10),
-        b'a'..=b'z' => Some(c - b'a' + 36),
-        b'!' => Some(62),
-        b'#' => Some(63),
-        b'$' => Some(64),
-        b'%' => Some(65),
-        b'&' => Some(66),
-        b'(' => Some(67),
-        b')' => Some(68),
-        b'*' => Some(69),
-        b'+' => Some(70),
-        b'-' => Some(71),
-        b';' => Some(72),
-        b'<' => Some(73),
-        b'=' => Some(74),
-        b'>' => Some(75),
-        b'?' => Some(76),
-        b'@' => Some(77),
-        b'^' => Some(78),
-        b'_' => Some(79),
-        b'`' => Some(80),
-        b'{' => Some(81),
-        b'|' => Some(82),
-        b'}' => Some(83),
-        b'~' => Some(84),
-        _ => None,
-    }
-}
-
-static BASE85_CHARS: &[u8; 85] =
+    match RFC1924_DIGITS[usize::from(c)] {
+        INVALID_DIGIT => None,
+        digit => Some(digit),
+    }
+}
+
+/// `is_valid_char()` tells whether `c` is part of the RFC1924 alphabet
+///
+/// Whitespaces, which are skipped during decoding, aren't part of the alphabet.
+///
+/// # Example
+///
+/// ```
+/// assert!(base85rs::is_valid_char(b'~'));
+/// assert!(!base85rs::is_valid_char(b']'));
+/// ```
+#[must_use]
+pub fn is_valid_char(c: u8) -> bool {
+    to_x85(c).is_some()
+}
+
+/// `char_value()` returns the value of `c` in the RFC1924 alphabet, from 0 to 84
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::char_value(b'A'), Some(10));
+/// assert_eq!(base85rs::char_value(b' '), None);
+/// ```
+#[must_use]
+pub fn char_value(c: u8) -> Option<u8> {
+    to_x85(c)
+}
+
+/// The 85 characters of the RFC1924 alphabet, in the order of their values
+pub const RFC1924_ALPHABET: &[u8; 85] =
     b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
 
 #[must_use]
-// Encode a single chunk. At most 4 bytes, at least 1.
+// Encode a single chunk, completed with zero bytes. At most 4 bytes, at least 1, the bytes past
+// the 4th one being ignored.
 fn encode_u32_chunk(chunk: &[u8], buffer: &mut [u8; 5]) -> usize {
-    let in_value = u32::from_be_bytes(match chunk.len() {
-        1 => [chunk[0], 0, 0, 0],
-        2 => [chunk[0], chunk[1], 0, 0],
-        3 => [chunk[0], chunk[1], chunk[2], 0],
-        4 => [chunk[0], chunk[1], chunk[2], chunk[3]],
-        _ => unreachable!(),
-    });
-
-    let in_value = usize::try_from(in_value).unwrap();
-
-    // Powers of 85: 85, 7_225, 614_125,52_200_625
-    *buffer = [
-        BASE85_CHARS[in_value / 52_200_625],
-        BASE85_CHARS[(in_value % 52_200_625) / 614_125],
-        BASE85_CHARS[(in_value % 614_125) / 7_225],
-        BASE85_CHARS[(in_value % 7_225_usize) / 85],
-        BASE85_CHARS[in_value % 85_usize],
-    ];
-    chunk.len()
+    let mut group = [0; 4];
+    for (byte, &chr) in group.iter_mut().zip(chunk) {
+        *byte = chr;
+    }
+    let in_value = u32::from_be_bytes(group);
+
+    // Every digit is below 85, the arithmetic stays in `u32` for targets with a 16 bits `usize`
+    let digit = |value: u32| RFC1924_CHARS[usize::from(value.to_le_bytes()[0])];
+
+    // Digits from the least significant one, each division being a multiplication
+    let mut value = in_value;
+    for slot in buffer.iter_mut().rev() {
+        let (quotient, remainder) = div_rem_85(value);
+        *slot = digit(remainder);
+        value = quotient;
+    }
+    chunk.len().min(4)
+}
+
+// Divide by 85 with a multiplication and a shift, exact for any `u32`: 3_233_857_729 is
+// 2^38 / 85 rounded up, and the rounding error stays below 2^6 over the whole `u32` range.
+// Unlike the divisions by the 4 powers of 85, this takes a single multiplication on cores without
+// a fast divider.
+#[inline]
+fn div_rem_85(value: u32) -> (u32, u32) {
+    let quotient = ((u64::from(value) * 3_233_857_729) >> 38) as u32;
+    (quotient, value - quotient * 85)
+}
+
+// Turn encoded characters, all ASCII, into a `String`. The `unsafe-fast` feature skips the UTF-8
+// validation.
+pub(crate) fn ascii_string(outdata: Vec<u8>) -> String {
+    #[cfg(feature = "unsafe-fast")]
+    {
+        debug_assert!(outdata.is_ascii());
+        // SAFETY: ASCII is valid UTF-8
+        unsafe { String::from_utf8_unchecked(outdata) }
+    }
+    #[cfg(not(feature = "unsafe-fast"))]
+    String::from_utf8(outdata).unwrap_or_default()
+}
+
+// Same as `ascii_string()`, for borrowed characters
+pub(crate) fn ascii_str(outdata: &[u8]) -> &str {
+    #[cfg(feature = "unsafe-fast")]
+    {
+        debug_assert!(outdata.is_ascii());
+        // SAFETY: ASCII is valid UTF-8
+        unsafe { ::core::str::from_utf8_unchecked(outdata) }
+    }
+    #[cfg(not(feature = "unsafe-fast"))]
+    ::core::str::from_utf8(outdata).unwrap_or_default()
 }
 
 /// `encode()` turns a slice of bytes into base85 encoded `String`
@@ -76,73 +271,566 @@ fn encode_u32_chunk(chunk: &[u8], buffer: &mut [u8; 5]) -> usize {
 /// ```
 #[must_use]
 pub fn encode(data: &[u8]) -> String {
+    let outdata = Vec::with_capacity(encoded_len(data.len()));
+
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    let (outdata, data) = {
+        let mut outdata = outdata;
+        let consumed = simd::encode_blocks(data, &mut outdata);
+        (outdata, &data[consumed..])
+    };
+
+    let mut outdata = outdata;
+    encode_extend(data, &mut outdata);
+    ascii_string(outdata)
+}
+
+/// `encode_all()` turns several slices of bytes into a single base85 encoded `String`
+///
+/// The slices are encoded as if they were concatenated, groups spanning over them, without
+/// copying them together first.
+///
+/// # Example
+///
+/// ```
+/// let encoded = base85rs::encode_all([b"aa".as_slice(), b"aaa"]);
+/// assert_eq!(encoded, base85rs::encode(b"aaaaa"));
+/// ```
+#[must_use]
+pub fn encode_all<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> String {
+    let mut outdata = Vec::new();
+    let mut encoder = incremental::Encoder::default();
+    for part in parts {
+        encoder.update(part, |chars| outdata.extend(chars));
+    }
+    encoder.finish(|chars| outdata.extend(chars));
+    ascii_string(outdata)
+}
+
+/// `encode_vectored()` turns the I/O slices of a scatter/gather buffer into a single base85
+/// encoded `String`
+///
+/// As with `encode_all()`, groups span over the slices, so fragmented packets encode like their
+/// concatenation. `EncoderWriter` implements `write_vectored()` the same way.
+///
+/// # Example
+///
+/// ```
+/// use std::io::IoSlice;
+///
+/// let encoded = base85rs::encode_vectored(&[IoSlice::new(b"a"), IoSlice::new(b"aaaa")]);
+/// assert_eq!(encoded, "VPRomVE");
+/// ```
+#[must_use]
+pub fn encode_vectored(slices: &[std::io::IoSlice<'_>]) -> String {
+    encode_all(slices.iter().map(|slice| &**slice))
+}
+
+/// `encode_padded()` turns a slice of bytes into base85 encoded `String`, completing the trailing
+/// partial group with zero bytes
+///
+/// This matches `base64.b85encode(data, pad=True)` of Python: the output is always a multiple of
+/// 5 characters. As in Python, decoding it returns the data followed by the padding bytes.
+///
+/// # Example
+///
+/// ```
+/// let encoded = base85rs::encode_padded(b"a");
+/// assert_eq!(encoded, "VE_OC");
+/// assert_eq!(base85rs::decode(&encoded), Some(vec![b'a', 0, 0, 0]));
+/// ```
+#[must_use]
+pub fn encode_padded(data: &[u8]) -> String {
     let mut buffer = [0; 5];
 
-    let outdata = data
-        .chunks(4)
-        .fold(Vec::with_capacity(data.len()), |mut acc, chunk| {
-            let c = encode_u32_chunk(chunk, &mut buffer);
-            acc.extend(buffer[0..=c].iter());
+    let outdata = data.chunks(4).fold(
+        Vec::with_capacity(encoded_len(data.len()) + 4),
+        |mut acc, chunk| {
+            let _ = encode_u32_chunk(chunk, &mut buffer);
+            acc.extend(buffer);
             acc
-        });
-    String::from_utf8(outdata).unwrap_or_default()
+        },
+    );
+    ascii_string(outdata)
+}
+
+/// `encode_grouped()` turns a slice of bytes into base85 encoded `String`, inserting `separator`
+/// every `every` characters
+///
+/// Grouped codes are easier to read and transcribe. Only a space separator is skipped by
+/// `decode()`, other separators have to be removed before decoding.
+///
+/// # Panics
+///
+/// Panics if `every` is 0.
+///
+/// # Example
+///
+/// ```
+/// let encoded = base85rs::encode_grouped(b"aaaaaaa", 5, ' ');
+/// assert_eq!(encoded, "VPRom VPRn");
+/// assert_eq!(base85rs::decode(&encoded), Some(b"aaaaaaa".to_vec()));
+/// ```
+#[must_use]
+pub fn encode_grouped(data: &[u8], every: usize, separator: char) -> String {
+    assert!(every != 0, "group length must not be 0");
+    let encoded = encode(data);
+    let mut outdata =
+        String::with_capacity(encoded.len() + encoded.len() / every * separator.len_utf8());
+    for (index, chr) in encoded.chars().enumerate() {
+        if index != 0 && index % every == 0 {
+            outdata.push(separator);
+        }
+        outdata.push(chr);
+    }
+    outdata
+}
+
+/// `encoded_len()` returns the number of characters needed to encode `len` bytes
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::encoded_len(4), 5);
+/// assert_eq!(base85rs::encoded_len(5), 7);
+/// ```
+#[must_use]
+pub const fn encoded_len(len: usize) -> usize {
+    let rem = len % 4;
+    len / 4 * 5 + if rem == 0 { 0 } else { rem + 1 }
+}
+
+/// `decoded_len()` returns the number of bytes produced by decoding `len` characters
+///
+/// Ignored whitespaces must not be counted, otherwise the returned value is only an upper bound.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::decoded_len(5), 4);
+/// assert_eq!(base85rs::decoded_len(7), 5);
+/// ```
+#[must_use]
+pub const fn decoded_len(len: usize) -> usize {
+    let rem = len % 5;
+    len / 5 * 4 + if rem == 0 { 0 } else { rem - 1 }
+}
+
+/// `encode_slice()` encodes a slice of bytes into `out`, returning the number of characters written
+///
+/// # Errors
+///
+/// Returns `EncodeError::OutputTooSmall` if `out` is shorter than `encoded_len(data.len())`.
+///
+/// # Example
+///
+/// ```
+/// let mut out = [0; 8];
+/// let written = base85rs::encode_slice(b"aaaaa", &mut out).unwrap();
+/// assert_eq!(&out[..written], b"VPRomVE");
+/// ```
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+pub fn encode_slice(data: &[u8], out: &mut [u8]) -> Result<usize, EncodeError> {
+    let out = out
+        .get_mut(..encoded_len(data.len()))
+        .ok_or(EncodeError::OutputTooSmall)?;
+
+    let mut buffer = [0; 5];
+    for (chunk, out_chunk) in data.chunks(4).zip(out.chunks_mut(5)) {
+        let _ = encode_u32_chunk(chunk, &mut buffer);
+        // `out_chunk` has the length of the encoded chunk
+        for (chr, &encoded) in out_chunk.iter_mut().zip(&buffer) {
+            *chr = encoded;
+        }
+    }
+    #[cfg(feature = "zeroize")]
+    ::zeroize::Zeroize::zeroize(&mut buffer);
+    Ok(out.len())
+}
+
+/// `encode_to_str()` encodes a slice of bytes into `out` and returns the encoded `&str`, borrowed
+/// from `out`
+///
+/// # Errors
+///
+/// Returns `EncodeError::OutputTooSmall` if `out` is shorter than `encoded_len(data.len())`.
+///
+/// # Example
+///
+/// ```
+/// let mut out = [0; 8];
+/// assert_eq!(base85rs::encode_to_str(b"aaaaa", &mut out), Ok("VPRomVE"));
+/// ```
+pub fn encode_to_str<'a>(data: &[u8], out: &'a mut [u8]) -> Result<&'a str, EncodeError> {
+    let written = encode_slice(data, out)?;
+    Ok(ascii_str(&out[..written]))
 }
 
-// Decode a single chunk. At most, 5 `u8`, at least one.
-fn decode_chunk(chunk: &[u8]) -> Option<[u8; 4]> {
-    chunk
+/// `encode_extend()` appends the base85 encoded characters of `data` to any `Extend<u8>` container
+///
+/// Characters are appended as each group is encoded, without any intermediate buffer: to a
+/// `Vec<u8>`, a `VecDeque<u8>` ring buffer, a `bytes::BytesMut`... Containers of fixed capacity,
+/// such as `heapless::Vec`, may panic when full: `encode_slice()` reports it instead.
+///
+/// # Example
+///
+/// ```
+/// let mut out = b"data=".to_vec();
+/// base85rs::encode_extend(b"aaaaa", &mut out);
+/// assert_eq!(out, b"data=VPRomVE");
+/// ```
+pub fn encode_extend(data: &[u8], out: &mut impl Extend<u8>) {
+    let mut buffer = [0; 5];
+    for chunk in data.chunks(4) {
+        let c = encode_u32_chunk(chunk, &mut buffer);
+        out.extend(buffer[..=c].iter().copied());
+    }
+}
+
+// Decode a single group of 5 digits, starting at `index` in the input, failing if its value
+// exceeds `u32::MAX`, such as `|NsC1`.
+pub(crate) fn decode_digits(digits: [u8; 5], index: usize) -> Result<[u8; 4], DecodeError> {
+    let value = digits
         .iter()
-        .try_fold(0, |mut acc, item| match to_x85(*item) {
-            Some(value) => {
-                acc *= 85;
-                acc += u32::from(value);
-                Some(acc)
-            }
-            _ => None,
-        })
+        .fold(0, |acc: u64, &digit| acc * 85 + u64::from(digit));
+    u32::try_from(value)
         .map(u32::to_be_bytes)
+        .map_err(|_| DecodeError::GroupOverflow { index })
+}
+
+/// `encode_group()` turns a single group of 4 bytes into its 5 base85 characters
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(&base85rs::encode_group(*b"aaaa"), b"VPRom");
+/// ```
+#[must_use]
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+pub fn encode_group(group: [u8; 4]) -> [u8; 5] {
+    let mut buffer = [0; 5];
+    let _ = encode_u32_chunk(&group, &mut buffer);
+    buffer
+}
+
+/// `decode_group()` try to decode a single group of 5 base85 characters into its 4 bytes
+///
+/// Unlike `decode()`, whitespaces aren't skipped.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if a character is outside of the alphabet and
+/// `DecodeError::GroupOverflow` if the group doesn't fit in 4 bytes.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::decode_group(*b"VPRom"), Ok(*b"aaaa"));
+/// assert!(base85rs::decode_group(*b"~~~~~").is_err());
+/// ```
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+pub fn decode_group(group: [u8; 5]) -> Result<[u8; 4], DecodeError> {
+    let mut digits = [0; 5];
+    for (index, (digit, &byte)) in digits.iter_mut().zip(&group).enumerate() {
+        *digit = to_x85(byte).ok_or(DecodeError::InvalidByte { index, byte })?;
+    }
+    decode_digits(digits, 0)
+}
+
+// Decode `instr`, skipping whitespaces, and feed every decoded group to `sink`.
+#[inline]
+fn decode_with_sink(
+    instr: &[u8],
+    mut sink: impl FnMut(&[u8]) -> Result<(), DecodeError>,
+) -> Result<(), DecodeError> {
+    let mut decoder = incremental::Decoder::default();
+    decoder.update(instr, &mut sink)?;
+    decoder.finish(sink)
 }
 
 /// `decode()` try to decode a base85 encoded &str and return an `Option<Vec<u8>>`
 ///
+/// Besides `&str`, any text or bytes input implementing `AsDecodeInput` is accepted, such as
+/// `String`, `&[u8]` or `Vec<u8>`.
+///
 /// # Example
 ///
 /// ```
 /// let data = "VE";
 /// let decoded = base85rs::decode(&data);
 /// assert_eq!(decoded, Some(vec![b'a']));
+/// assert_eq!(base85rs::decode(b"VE".to_vec()), Some(vec![b'a']));
 /// ```
 #[must_use]
-pub fn decode(instr: &str) -> Option<Vec<u8>> {
-    let data: Vec<u8> = instr
-        .as_bytes()
-        .iter()
-        .filter(|&chr| *chr != 0x20)
-        .copied()
-        .collect();
+pub fn decode(instr: impl AsDecodeInput) -> Option<Vec<u8>> {
+    decode_vec(instr.as_decode_input()).ok()
+}
 
-    let mut outdata = Vec::<u8>::new();
+fn decode_vec(instr: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut outdata = Vec::<u8>::with_capacity(decoded_len(instr.len()));
+    let mut decoder = incremental::Decoder::default();
 
-    for chunk in data.chunks_exact(5) {
-        let value = decode_chunk(chunk)?;
-        outdata.extend(value);
-    }
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    let instr = {
+        let consumed = simd::decode_blocks(instr, &mut outdata);
+        decoder.set_index(consumed);
+        &instr[consumed..]
+    };
+
+    let mut sink = |bytes: &[u8]| {
+        outdata.extend(bytes);
+        Ok(())
+    };
+    decoder.update(instr, &mut sink)?;
+    decoder.finish(sink)?;
+    Ok(outdata)
+}
+
+/// `decode_slice()` decodes a base85 encoded &str into `out`, returning the number of bytes written
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX` and `DecodeError::OutputTooSmall` if
+/// `out` can't hold the decoded data.
+///
+/// # Example
+///
+/// ```
+/// let mut out = [0; 8];
+/// let written = base85rs::decode_slice("VPRomVE", &mut out).unwrap();
+/// assert_eq!(&out[..written], b"aaaaa");
+/// ```
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+pub fn decode_slice(instr: &str, out: &mut [u8]) -> Result<usize, DecodeError> {
+    decode_into(instr.as_bytes(), out)
+}
+
+/// `decode_with_limit()` try to decode a base85 encoded &str, producing at most `max_bytes` bytes
+///
+/// Decoding stops as soon as the output would exceed the limit, which makes it suitable for
+/// untrusted inputs. The output buffer never grows beyond `max_bytes` either.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX` and `DecodeError::LimitExceeded`
+/// if the decoded data is longer than `max_bytes`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::decode_with_limit("VPRomVE", 5).unwrap(), b"aaaaa");
+/// assert!(base85rs::decode_with_limit("VPRomVE", 4).is_err());
+/// ```
+pub fn decode_with_limit(instr: &str, max_bytes: usize) -> Result<Vec<u8>, DecodeError> {
+    let mut outdata = Vec::with_capacity(decoded_len(instr.len()).min(max_bytes));
+    decode_with_sink(instr.as_bytes(), |bytes| {
+        if outdata.len() + bytes.len() > max_bytes {
+            return Err(DecodeError::LimitExceeded);
+        }
+        outdata.extend(bytes);
+        Ok(())
+    })?;
+    Ok(outdata)
+}
 
-    let rem = data.len() % 5;
-    if rem != 0 {
-        let in_index = &data[data.len() - rem..];
-        let chunk_len = in_index.len();
-        let mut in_index = in_index.to_vec();
+#[inline]
+fn decode_into(instr: &[u8], out: &mut [u8]) -> Result<usize, DecodeError> {
+    let mut written = 0;
+    decode_with_sink(instr, |bytes| {
+        let slots = out
+            .get_mut(written..written + bytes.len())
+            .ok_or(DecodeError::OutputTooSmall)?;
+        for (slot, &byte) in slots.iter_mut().zip(bytes) {
+            *slot = byte;
+        }
+        written += bytes.len();
+        Ok(())
+    })?;
+    Ok(written)
+}
+
+/// `decode_uninit()` decodes a base85 encoded &str into the uninitialized buffer `out` and returns
+/// the initialized prefix, holding the decoded data
+///
+/// This spares zero-filling a large buffer before decoding into it. Only available with the
+/// `unsafe-fast` feature, the crate forbids unsafe code otherwise.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX` and `DecodeError::OutputTooSmall` if
+/// `out` can't hold the decoded data.
+///
+/// # Example
+///
+/// ```
+/// let mut out = Vec::with_capacity(8);
+/// let decoded = base85rs::decode_uninit("VPRomVE", out.spare_capacity_mut()).unwrap();
+/// assert_eq!(decoded, b"aaaaa");
+/// ```
+#[cfg(feature = "unsafe-fast")]
+pub fn decode_uninit<'a>(
+    instr: &str,
+    out: &'a mut [::core::mem::MaybeUninit<u8>],
+) -> Result<&'a mut [u8], DecodeError> {
+    let mut written = 0;
+    decode_with_sink(instr.as_bytes(), |bytes| {
+        let slots = out
+            .get_mut(written..written + bytes.len())
+            .ok_or(DecodeError::OutputTooSmall)?;
+        for (slot, &byte) in slots.iter_mut().zip(bytes) {
+            slot.write(byte);
+        }
+        written += bytes.len();
+        Ok(())
+    })?;
+    // SAFETY: the first `written` bytes of `out` have been initialized above
+    Ok(unsafe { ::core::slice::from_raw_parts_mut(out.as_mut_ptr().cast::<u8>(), written) })
+}
 
-        while in_index.len() != 5 {
-            in_index.push(126);
+/// `decode_in_place()` try to decode the base85 characters of `buffer`, overwriting it with the
+/// decoded bytes
+///
+/// The decoded data is always shorter than its encoded form, it is written at the start of
+/// `buffer` and its length is returned. The rest of `buffer` is left in an unspecified state.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet
+/// and `DecodeError::GroupOverflow` if a group exceeds `u32::MAX`.
+///
+/// # Example
+///
+/// ```
+/// let mut buffer = b"VPRomVE".to_vec();
+/// let len = base85rs::decode_in_place(&mut buffer).unwrap();
+/// buffer.truncate(len);
+/// assert_eq!(buffer, b"aaaaa");
+/// ```
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+pub fn decode_in_place(buffer: &mut [u8]) -> Result<usize, DecodeError> {
+    let mut decoder = incremental::Decoder::default();
+    let mut written = 0;
+    let mut sink = |bytes: &[u8], buffer: &mut [u8]| {
+        // At least 5 characters have been read for every 4 bytes written, the slots exist
+        let slots = buffer.get_mut(written..).unwrap_or_default();
+        for (slot, &byte) in slots.iter_mut().zip(bytes) {
+            *slot = byte;
         }
+        written += bytes.len();
+        Ok(())
+    };
+    for index in 0..buffer.len() {
+        let chr = [buffer.get(index).copied().unwrap_or_default()];
+        decoder.update(&chr, |bytes| sink(bytes, buffer))?;
+    }
+    decoder.finish(|bytes| sink(bytes, buffer))?;
+    Ok(written)
+}
+
+/// `encode_array()` turns a fixed-size array of bytes into a fixed-size array of base85 characters
+///
+/// The output size `M` must be `encoded_len(N)`, this is checked at compile time.
+///
+/// # Example
+///
+/// ```
+/// let encoded: [u8; 7] = base85rs::encode_array(b"aaaaa");
+/// assert_eq!(&encoded, b"VPRomVE");
+/// ```
+///
+/// ```compile_fail
+/// let encoded: [u8; 8] = base85rs::encode_array(b"aaaaa");
+/// ```
+#[must_use]
+pub fn encode_array<const N: usize, const M: usize>(data: &[u8; N]) -> [u8; M] {
+    const { assert!(M == encoded_len(N), "output size must be encoded_len(N)") };
+
+    let mut out = [0; M];
+    // Can't fail, the output size has been checked above
+    let _ = encode_slice(data, &mut out);
+    out
+}
+
+/// `decode_array()` try to decode a fixed-size array of base85 characters into an array of bytes
+///
+/// The input size `M` must be `encoded_len(N)`, this is checked at compile time.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX` and `DecodeError::InvalidLength`
+/// if it contains whitespaces, as they make the decoded data shorter than `N`.
+///
+/// # Example
+///
+/// ```
+/// let decoded: [u8; 5] = base85rs::decode_array(b"VPRomVE").unwrap();
+/// assert_eq!(&decoded, b"aaaaa");
+/// ```
+///
+/// ```compile_fail
+/// let decoded: [u8; 4] = base85rs::decode_array(b"VPRomVE").unwrap();
+/// ```
+pub fn decode_array<const M: usize, const N: usize>(
+    instr: &[u8; M],
+) -> Result<[u8; N], DecodeError> {
+    const { assert!(M == encoded_len(N), "input size must be encoded_len(N)") };
 
-        let accumulator = decode_chunk(&in_index)?;
-        outdata.extend(&accumulator[0..chunk_len - 1]);
+    let mut out = [0; N];
+    if decode_into(instr, &mut out)? == N {
+        Ok(out)
+    } else {
+        Err(DecodeError::InvalidLength)
+    }
+}
+
+/// `decode_exact()` try to decode a base85 encoded &str into exactly `N` bytes
+///
+/// Unlike `decode_array()`, the input length isn't fixed: whitespaces are skipped.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX` and `DecodeError::InvalidLength`
+/// if it doesn't decode to exactly `N` bytes.
+///
+/// # Example
+///
+/// ```
+/// let key: [u8; 5] = base85rs::decode_exact("VPRom VE").unwrap();
+/// assert_eq!(&key, b"aaaaa");
+/// assert!(base85rs::decode_exact::<4>("VPRomVE").is_err());
+/// ```
+pub fn decode_exact<const N: usize>(instr: &str) -> Result<[u8; N], DecodeError> {
+    let mut out = [0; N];
+    match decode_into(instr.as_bytes(), &mut out) {
+        Ok(written) if written == N => Ok(out),
+        Ok(_) | Err(DecodeError::OutputTooSmall) => Err(DecodeError::InvalidLength),
+        Err(err) => Err(err),
     }
+}
 
-    Some(outdata)
+/// `decode_to_string()` try to decode a base85 encoded &str into UTF-8 text
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX` and `DecodeError::InvalidUtf8` if the
+/// decoded data isn't valid UTF-8.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::decode_to_string("VPRomVE").unwrap(), "aaaaa");
+/// assert!(base85rs::decode_to_string("|NsC0").is_err());
+/// ```
+pub fn decode_to_string(instr: &str) -> Result<String, DecodeError> {
+    String::from_utf8(decode_vec(instr.as_bytes())?).map_err(|err| DecodeError::InvalidUtf8 {
+        valid_up_to: err.utf8_error().valid_up_to(),
+    })
 }
 
 #[cfg(test)]
@@ -265,4 +953,352 @@ mod tests {
     fn decode_invalid() {
         assert!(decode("]").is_none())
     }
+
+    #[test]
+    fn div_rem_85_exact() {
+        let values = (0..100_000)
+            .chain((0..=u32::MAX).step_by(65_537))
+            .chain(u32::MAX - 100_000..=u32::MAX);
+        for value in values {
+            assert_eq!(div_rem_85(value), (value / 85, value % 85), "{value}");
+        }
+    }
+
+    #[test]
+    fn encode_vectored_slices() {
+        use std::io::IoSlice;
+
+        let data: Vec<u8> = (0..=255).collect();
+        for split in [0, 1, 3, 4, 7, 255] {
+            let (head, tail) = data.split_at(split);
+            let (middle, tail) = tail.split_at(1);
+            let slices = [IoSlice::new(head), IoSlice::new(middle), IoSlice::new(tail)];
+            assert_eq!(encode_vectored(&slices), encode(&data));
+        }
+        assert_eq!(encode_vectored(&[]), "");
+    }
+
+    #[test]
+    fn encode_all_parts() {
+        let data = b"countercompetition";
+        assert_eq!(encode_all([]), "");
+        assert_eq!(encode_all([data.as_slice()]), encode(data));
+        assert_eq!(
+            encode_all(data.chunks(3).chain([b"".as_slice()])),
+            encode(data)
+        );
+    }
+
+    #[test]
+    fn encode_padded_python_vectors() {
+        assert_eq!(encode_padded(b""), "");
+        assert_eq!(encode_padded(b"a"), "VE_OC");
+        assert_eq!(encode_padded(b"abcd"), "VPa!s");
+        assert_eq!(encode_padded(b"aaaaa"), "VPRomVE_OC");
+        assert_eq!(decode("VPRomVE_OC"), Some(b"aaaaa\0\0\0".to_vec()));
+    }
+
+    #[test]
+    fn alphabet_membership() {
+        for (value, &c) in (0..).zip(RFC1924_ALPHABET.iter()) {
+            assert!(is_valid_char(c));
+            assert_eq!(char_value(c), Some(value));
+        }
+        let invalid = (0..=u8::MAX).filter(|&c| !is_valid_char(c)).count();
+        assert_eq!(invalid, 256 - 85);
+    }
+
+    #[test]
+    fn decode_overflow() {
+        // `|NsC0` is `u32::MAX`, anything above it doesn't fit in a group
+        assert_eq!(decode("|NsC0"), Some(vec![0xFF; 4]));
+        for (instr, index) in [
+            ("|NsC1", 0),
+            ("|NsD0", 0),
+            ("~~~~~", 0),
+            ("VPRom|NsC1", 5),
+            ("VPRom |Nt", 6),
+        ] {
+            assert_eq!(
+                decode_vec(instr.as_bytes()),
+                Err(DecodeError::GroupOverflow { index }),
+                "{instr}"
+            );
+        }
+        // The highest valid partial groups
+        assert_eq!(decode("|Ns9"), Some(vec![0xFF; 3]));
+        assert_eq!(decode("|NsC"), None);
+        let mut out = [0; 8];
+        assert_eq!(
+            decode_slice("|NsC1", &mut out),
+            Err(DecodeError::GroupOverflow { index: 0 })
+        );
+        let mut buffer = *b"VPRom~~~~~";
+        assert_eq!(
+            decode_in_place(&mut buffer),
+            Err(DecodeError::GroupOverflow { index: 5 })
+        );
+    }
+
+    #[test]
+    fn group_roundtrip() {
+        for group in [[0; 4], [0xFF; 4], *b"aaaa", [1, 2, 3, 4]] {
+            assert_eq!(decode_group(encode_group(group)), Ok(group));
+        }
+        assert_eq!(&encode_group([0xFF; 4]), b"|NsC0");
+        assert_eq!(
+            decode_group(*b"|NsC1"),
+            Err(DecodeError::GroupOverflow { index: 0 })
+        );
+        assert_eq!(
+            decode_group(*b"VP Ro"),
+            Err(DecodeError::InvalidByte {
+                index: 2,
+                byte: b' '
+            })
+        );
+    }
+
+    #[cfg(feature = "unsafe-fast")]
+    #[test]
+    fn decode_uninit_prefix() {
+        let mut out = [::core::mem::MaybeUninit::uninit(); 8];
+        assert_eq!(decode_uninit("VPRom VE", &mut out).unwrap(), b"aaaaa");
+        assert_eq!(decode_uninit("", &mut out).unwrap(), b"");
+        assert_eq!(
+            decode_uninit("VPRomVPRom", &mut out[..7]),
+            Err(DecodeError::OutputTooSmall)
+        );
+    }
+
+    #[test]
+    fn encode_to_str_borrows_out() {
+        let mut out = [b'x'; 10];
+        assert_eq!(encode_to_str(b"aaaaa", &mut out), Ok("VPRomVE"));
+        assert_eq!(&out[7..], b"xxx");
+        assert_eq!(encode_to_str(b"", &mut out), Ok(""));
+        assert_eq!(
+            encode_to_str(b"aaaaa", &mut out[..6]),
+            Err(EncodeError::OutputTooSmall)
+        );
+    }
+
+    #[test]
+    fn encode_extend_containers() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut ring = std::collections::VecDeque::from(vec![b'>']);
+        encode_extend(&data, &mut ring);
+        assert_eq!(ring.pop_front(), Some(b'>'));
+        assert_eq!(Vec::from(ring), encode(&data).into_bytes());
+
+        let mut out = Vec::new();
+        encode_extend(b"", &mut out);
+        encode_extend(b"a", &mut out);
+        assert_eq!(out, b"VE");
+    }
+
+    #[test]
+    fn decode_to_string_utf8() {
+        let encoded = encode("flag{😀}".as_bytes());
+        assert_eq!(decode_to_string(&encoded).unwrap(), "flag{😀}");
+        assert_eq!(
+            decode_to_string(&encode(b"ab\xffcd")),
+            Err(DecodeError::InvalidUtf8 { valid_up_to: 2 })
+        );
+    }
+
+    #[test]
+    fn errors_box_into_core_error() {
+        fn decode_boxed(instr: &str) -> Result<[u8; 4], Box<dyn ::core::error::Error>> {
+            Ok(decode_exact(instr)?)
+        }
+        assert_eq!(decode_boxed("VPRom").unwrap(), *b"aaaa");
+        assert_eq!(
+            decode_boxed("VPRomVE").unwrap_err().to_string(),
+            DecodeError::InvalidLength.to_string()
+        );
+    }
+
+    #[test]
+    fn decode_exact_lengths() {
+        assert_eq!(decode_exact::<0>(""), Ok([]));
+        assert_eq!(decode_exact::<4>("VPRom"), Ok(*b"aaaa"));
+        assert_eq!(
+            decode_exact::<6>("VPRomVE"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            decode_exact::<4>("VPRomVE"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            decode_exact::<5>("VPR]mVE"),
+            Err(DecodeError::InvalidByte {
+                index: 3,
+                byte: b']'
+            })
+        );
+    }
+
+    #[test]
+    fn encode_chunk_extremes() {
+        let mut buffer = [0; 5];
+        for (chunk, expected) in [
+            (&[0xFF][..], &b"{{"[..]),
+            (&[0xFF; 4][..], b"|NsC0"),
+            (&[0][..], b"00"),
+        ] {
+            let len = encode_u32_chunk(chunk, &mut buffer);
+            assert_eq!(&buffer[..=len], expected);
+        }
+    }
+
+    #[test]
+    fn encode_grouped_separators() {
+        assert_eq!(encode_grouped(b"", 5, '-'), "");
+        assert_eq!(encode_grouped(b"aaaa", 5, '-'), "VPRom");
+        assert_eq!(encode_grouped(b"aaaaa", 5, '-'), "VPRom-VE");
+        assert_eq!(
+            encode_grouped(b"aaaaa", 2, '\u{2009}'),
+            "VP\u{2009}Ro\u{2009}mV\u{2009}E"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "group length must not be 0")]
+    fn encode_grouped_zero() {
+        let _ = encode_grouped(b"aaaa", 0, ' ');
+    }
+
+    #[test]
+    fn encode_slice_too_small() {
+        let mut out = [0; 6];
+        assert_eq!(
+            encode_slice("aaaaa".as_bytes(), &mut out),
+            Err(EncodeError::OutputTooSmall)
+        );
+    }
+
+    #[test]
+    fn decode_slice_with_whitespace() {
+        let mut out = [0; 16];
+        let written = decode_slice("aBp mEWo~ R`b8`", &mut out).unwrap();
+        assert_eq!(&out[..written], "pollenless".as_bytes());
+    }
+
+    #[test]
+    fn decode_slice_too_small() {
+        let mut out = [0; 4];
+        assert_eq!(
+            decode_slice("VPRomVE", &mut out),
+            Err(DecodeError::OutputTooSmall)
+        );
+    }
+
+    // Shared by every function decoding through `decode_with_sink()`
+    #[test]
+    fn decode_with_sink_errors() {
+        let mut outdata = Vec::<u8>::new();
+        assert_eq!(
+            decode_with_sink(b"VPRomVP]", |bytes| {
+                outdata.extend(bytes);
+                Ok(())
+            }),
+            Err(DecodeError::InvalidByte {
+                index: 7,
+                byte: b']'
+            })
+        );
+        assert_eq!(outdata, b"aaaa");
+        assert_eq!(
+            decode_with_sink(b"VPRom|NsC1", |_| Ok(())),
+            Err(DecodeError::GroupOverflow { index: 5 })
+        );
+
+        // An error of the sink stops decoding
+        let mut calls = 0;
+        assert_eq!(
+            decode_with_sink(b"VPRomVPRom", |_| {
+                calls += 1;
+                Err(DecodeError::OutputTooSmall)
+            }),
+            Err(DecodeError::OutputTooSmall)
+        );
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn decode_with_limit_word_set() {
+        assert_eq!(
+            decode_with_limit("a%F63ZE192bZKvH", 12).unwrap(),
+            "relimitation".as_bytes()
+        );
+        assert_eq!(
+            decode_with_limit("a%F63ZE192bZKvH", 11),
+            Err(DecodeError::LimitExceeded)
+        );
+        assert_eq!(decode_with_limit("", 0).unwrap(), b"");
+    }
+
+    #[test]
+    fn decode_with_limit_stops_early() {
+        // The invalid character comes after the limit is hit
+        assert_eq!(
+            decode_with_limit("VPRomVPRom]", 4),
+            Err(DecodeError::LimitExceeded)
+        );
+    }
+
+    #[test]
+    fn decode_in_place_word_set() {
+        for (data, encoded) in [
+            ("", ""),
+            ("a", "VE"),
+            ("countercompetition", "V{dhCbY *g5Z*6d8bZK ;HZ*B"),
+        ] {
+            let mut buffer = encoded.as_bytes().to_vec();
+            let len = decode_in_place(&mut buffer).unwrap();
+            assert_eq!(&buffer[..len], data.as_bytes());
+        }
+
+        let mut buffer = b"VPR]".to_vec();
+        assert_eq!(
+            decode_in_place(&mut buffer),
+            Err(DecodeError::InvalidByte {
+                index: 3,
+                byte: b']'
+            })
+        );
+    }
+
+    #[test]
+    fn encode_array_word_set() {
+        assert_eq!(&encode_array::<10, 13>(b"pollenless"), b"aBpmEWo~R`b8`");
+        assert_eq!(&encode_array::<0, 0>(&[]), b"");
+    }
+
+    #[test]
+    fn decode_array_word_set() {
+        assert_eq!(
+            &decode_array::<13, 10>(b"aBpmEWo~R`b8`").unwrap(),
+            b"pollenless"
+        );
+        assert_eq!(decode_array::<7, 5>(b"VPRomVE").unwrap(), *b"aaaaa");
+    }
+
+    #[test]
+    fn decode_array_with_whitespace() {
+        assert_eq!(
+            decode_array::<7, 5>(b"VPRo VE"),
+            Err(DecodeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn encoded_and_decoded_len() {
+        for len in 0..32 {
+            assert_eq!(encoded_len(len), encode(&vec![0; len]).len());
+            assert_eq!(decoded_len(encoded_len(len)), len);
+        }
+    }
 }
@@ -0,0 +1,217 @@
+//! Differential checks against reference implementations
+//!
+//! The vectors below were generated from Python's `base64.b85encode()` / `base64.a85encode()`
+//! and from the reference Z85 encoder of libzmq, for a fixed set of inputs: edge cases and
+//! pseudo-random payloads of 1 to 100 bytes. Every variant is checked against them, both ways.
+
+// Inputs, hex encoded
+const INPUTS: &[&str] = &[
+    "",
+    "61",
+    "48656c6c6f20576f726c6421",
+    "00000000",
+    "00000000000000",
+    "20202020",
+    "ffffffff",
+    "ffffffffffffffffff",
+    "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9fa0a1a2a3a4a5a6a7a8a9aaabacadaeafb0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecfd0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeeff0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+    "73",
+    "6ee1",
+    "c44fe3",
+    "5b59d6f3",
+    "8ecec80c77",
+    "bcd951f7c540",
+    "363b98fedeeda2",
+    "ef341c9592cbec9a",
+    "9876fd552ea79c9c0a",
+    "88acf737db52d7a19263",
+    "97602d8f33c4d84acb2540",
+    "7ed66739f2254f11789e89b1",
+    "d9b6757981abb5d9eb8777b0ca",
+    "d58365fc2004c6302f16a3510313",
+    "29b98d9d00172f6b241cf9d584e8a0",
+    "1d5c383d83534ccc0754995ffc2c3325",
+    "51e8f91e9fec7e9cf2d4ba79570b751a2a2c6f261c1b500606e2521d714db0",
+    "c4e146ce0ce6ee2533e07ff4e295e4a82c74150847a6342008da69f320cd7ee0",
+    "199c39d00ec97d252d0f1f62eee6899a10d4119a587a17d6095ae114226936da5a",
+    "58bc98dca21296c256ac9c53a27263fd2318be11ee3b88415e414cd99cedb7c0efc4be2ec9238f2a8d1d39cb21161a2b38210c295c194fe7be8134ffbe1c8f83384cdabb942b299e8c6bd20cbceed8d1eb241c5a1b284235529a644a273a79db0b49833d",
+];
+
+// `base64.b85encode(data)`
+const B85ENCODE: &[&str] = &[
+    "",
+    "VE",
+    "NM&qnZy;B1a%^NF",
+    "00000",
+    "000000000",
+    "ARr(h",
+    "|NsC0",
+    "|NsC0|NsC0{{",
+    "009C61O)~M2nh-c3=Iws5D^j+6crX17#SKH9337XAR!_nBqb&%C@Cr{EG;fCFflSSG&MFiI5|2yJUu=?KtV!7L`6nNNJ&adOifNtP*GA-R8>}2SXo+ITwPvYU}0ioWMyV&XlZI|Y;A6DaB*^Tbai%jczJqze0_d@fPsR8goTEOh>41ejE#<ukdcy;l$Dm3n3<ZJoSmMZprN9pq@|{(sHv)}tgWuEu(7hUw6(UkxVgH!yuH4^z`?@9#Kp$P$jQpf%+1cv(9zP<)YaD4*xB0K+}+;a;Njxq<mKk)=;`X~?CtLF@bU8V^!4`l`1$(#{Qds_",
+    "a{",
+    "Zs7",
+    "#82Y",
+    "TUplg",
+    "j?TyocK",
+    "yxCFr#Xt",
+    "HanR9-tD3",
+    "?=&2hlFRIx",
+    "n0EbDE~lKF3I",
+    "h^+TF+fvt|l4A",
+    "mtZZAGsM_R%OyY",
+    "e%5C>@+D6Zc%F%|",
+    "*|v3gfvdII>xXx+$^",
+    ")q`dHAOyxRFBYRw0}}",
+    "DY=cE02eQ7BpmtGgy^6",
+    "9b7m)gHue*2UM9~{46si",
+    "QRw*|pX`2|^3=L{R||C-DlBg%92-yu2I5j3aZRu",
+    "#NkHH4Cd}7GvI&p;+5p6EOZqJN2W9&2-<1$AkBW@",
+    "8Js!L4#|BbEe{`J?&gV_5Y!QxSb7)M30mP4B55|-S^",
+    "SiG3rq7s(ER;-*;qH<&XBN)CB?mLJ<UO`ORob9*3@5H_?$s>;{jU745Ar=}dI3WxvTp3U2zJWCVz8sH(I855Rlq)HojBC;iyzbc1>m(dn8z@3GQkrB+CpvlC3rT}L",
+];
+
+// `base64.b85encode(data, pad=True)`
+const B85ENCODE_PAD: &[&str] = &[
+    "",
+    "VE_OC",
+    "NM&qnZy;B1a%^NF",
+    "00000",
+    "0000000000",
+    "ARr(h",
+    "|NsC0",
+    "|NsC0|NsC0{{R30",
+    "009C61O)~M2nh-c3=Iws5D^j+6crX17#SKH9337XAR!_nBqb&%C@Cr{EG;fCFflSSG&MFiI5|2yJUu=?KtV!7L`6nNNJ&adOifNtP*GA-R8>}2SXo+ITwPvYU}0ioWMyV&XlZI|Y;A6DaB*^Tbai%jczJqze0_d@fPsR8goTEOh>41ejE#<ukdcy;l$Dm3n3<ZJoSmMZprN9pq@|{(sHv)}tgWuEu(7hUw6(UkxVgH!yuH4^z`?@9#Kp$P$jQpf%+1cv(9zP<)YaD4*xB0K+}+;a;Njxq<mKk)=;`X~?CtLF@bU8V^!4`l`1$(#{Qds_",
+    "a{vGU",
+    "Zs7m`",
+    "#82Y@",
+    "TUplg",
+    "j?TyocK`qY",
+    "yxCFr#XtZ6",
+    "HanR9-tD3Q",
+    "?=&2hlFRIx",
+    "n0EbDE~lKF3IG5A",
+    "h^+TF+fvt|l4Ae>",
+    "mtZZAGsM_R%OyYn",
+    "e%5C>@+D6Zc%F%|",
+    "*|v3gfvdII>xXx+$^ZZW",
+    ")q`dHAOyxRFBYRw0}}uM",
+    "DY=cE02eQ7BpmtGgy^6E",
+    "9b7m)gHue*2UM9~{46si",
+    "QRw*|pX`2|^3=L{R||C-DlBg%92-yu2I5j3aZRuQ",
+    "#NkHH4Cd}7GvI&p;+5p6EOZqJN2W9&2-<1$AkBW@",
+    "8Js!L4#|BbEe{`J?&gV_5Y!QxSb7)M30mP4B55|-S^xk5",
+    "SiG3rq7s(ER;-*;qH<&XBN)CB?mLJ<UO`ORob9*3@5H_?$s>;{jU745Ar=}dI3WxvTp3U2zJWCVz8sH(I855Rlq)HojBC;iyzbc1>m(dn8z@3GQkrB+CpvlC3rT}L",
+];
+
+// `base64.a85encode(data)`
+#[cfg(feature = "ascii85")]
+const A85ENCODE: &[&str] = &[
+    "",
+    "@/",
+    "87cURD]i,\"Ebo80",
+    "z",
+    "z!!!!",
+    "+<VdL",
+    "s8W-!",
+    "s8W-!s8W-!rr",
+    "!!*-'\"9eu7#RLhG$k3[W&.oNg'GVB\"(`=52*$$(B+<_pR,UFcb-n-Vr/1iJ-0JP==1c70M3&s#]4?Ykm5X@_(6q'R884cEH9MJ8X:f1+h<)lt#=BSg3>[:ZC?t!MSA7]@cBPD3sCi+'.E,fo>FEMbNG^4U^I!pHnJ:W<)KS>/9Ll%\"IN/`jYOHG]iPa.Q$R$jD4S=Q7DTV8*TUnsrdW2ZetXKAY/Yd(L?['d?O\\@K2_]Y2%o^qmn*`5Ta:aN;TJbg\"GZd*^:jeCE.%f\\,!5gtgiEi8N\\UjQ5OekiqBum-X60nF?)@o_%qPq\"ad`r;HWp",
+    "Er",
+    "DW(",
+    "`)#C",
+    ">?TPK",
+    "Nm>]SG5",
+    "]\\-0V`BX",
+    "2ER<*hX.$",
+    "mkc#LP0<3\\",
+    "R!/F./uP50$3",
+    "Log>0gJZXsP%+",
+    "QXDD+1W7p<b9]C",
+    "Ib&-lng.'DGb0bs",
+    "fsZ$KJZH33l\\B\\gao",
+    "eUqH2+9]\\<0,C<[!tt",
+    ".CkG/!#I;(,TQX1K]o'",
+    "*F(QeK2YIf#?7*ur%'WM",
+    ";<[fsTBq#so$k6r<ss-h.P,Kb*#h]Y#3&N$ED<Y",
+    "`8O22%-Ht(1Z3cTig&T'/9DU48#A*c#hj\"a+O,An",
+    ")4W_6%`s,F/Irq4mcK@p&C_;\\=F(e7$!Q:%,&&sh=o",
+    "=M1$VU(Wd/<ihfiU2jcB,8e-,mQ64j?9q9<SF*f$n&2pmaWlirN?(%&+VktH3$A\\Z>T$?#^4A-@^)W2d3)&&<PUe2SN,-iM]^FG\"lQdHR)^n$1;OV,g-TZP-$V>t6",
+];
+
+// Reference encoder of ZeroMQ RFC 32, for the inputs of a multiple of 4 bytes
+#[cfg(feature = "z85")]
+const Z85_ENCODE: &[(usize, &str)] = &[
+    (0, ""),
+    (2, "nm=QNzY&b1A+]nf"),
+    (3, "00000"),
+    (5, "arR^H"),
+    (6, "%nSc0"),
+    (8, "009c61o!#m2NH?C3>iWS5d]J*6CRx17-skh9337xar.{NbQB=+c[cR@eg&FcfFLssg=mfIi5%2YjuU>)kTv.7l}6Nnnj=ADoIFnTp/ga?r8($2sxO*itWpVyu$0IOwmYv=xLzi%y&a6dAb/]tBAI+JCZjQZE0{D[FpSr8GOteoH(41EJe-<UKDCY&L:dM3N3<zjOsMmzPRn9PQ[%@^ShV!$TGwUeU^7HuW6^uKXvGh.YUh4]Z})[9-kP:p:JqPF+*1CV^9Zp<!yAd4/Xb0k*$*&A&nJXQ<MkK!>&}x#)cTlf[Bu8v].4}L}1:^-@qDS{"),
+    (12, "tuPLG"),
+    (16, ")>=2HLfriX"),
+    (20, "E+5c([*d6zC+f+%"),
+    (24, "9B7M!GhUE/2um9#@46SI"),
+    (26, "-nKhh4cD$7gVi=P&*5P6eozQjn2w9=2?<1:aKbw["),
+    (28, "sIg3RQ7S^er&?/&Qh<=xbn!cb)Mlj<uo}orOB9/3[5h{):S(&@Ju745aR>$Di3wXVtP3u2ZjwcvZ8Sh^i855rLQ!hOJbc&IYZBC1(M^DN8Z[3gqKRb*cPVLc3Rt$l"),
+];
+
+fn input(index: usize) -> Vec<u8> {
+    let hex = INPUTS[index].as_bytes();
+    hex.chunks(2)
+        .map(|pair| u8::from_str_radix(core::str::from_utf8(pair).unwrap(), 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn oracle_b85encode() {
+    for (index, &expected) in B85ENCODE.iter().enumerate() {
+        let data = input(index);
+        assert_eq!(crate::encode(&data), expected, "input {index}");
+        assert_eq!(crate::decode(expected), Some(data), "input {index}");
+    }
+}
+
+#[test]
+fn oracle_b85encode_pad() {
+    for (index, &expected) in B85ENCODE_PAD.iter().enumerate() {
+        let mut data = input(index);
+        assert_eq!(crate::encode_padded(&data), expected, "input {index}");
+        data.resize(data.len().next_multiple_of(4), 0);
+        assert_eq!(crate::decode(expected), Some(data), "input {index}");
+    }
+}
+
+#[cfg(feature = "ascii85")]
+#[test]
+fn oracle_a85encode() {
+    for (index, &expected) in A85ENCODE.iter().enumerate() {
+        let data = input(index);
+        assert_eq!(crate::ascii85::encode(&data), expected, "input {index}");
+        assert_eq!(
+            crate::ascii85::decode(expected).unwrap(),
+            data,
+            "input {index}"
+        );
+    }
+}
+
+#[cfg(feature = "z85")]
+#[test]
+fn oracle_z85() {
+    assert_eq!(
+        crate::z85::encode(&[0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B]),
+        "HelloWorld"
+    );
+    for &(index, expected) in Z85_ENCODE {
+        let data = input(index);
+        assert_eq!(
+            crate::z85::encode_strict(&data).unwrap(),
+            expected,
+            "input {index}"
+        );
+        assert_eq!(
+            crate::z85::decode_strict(expected).unwrap(),
+            data,
+            "input {index}"
+        );
+    }
+}
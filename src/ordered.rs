@@ -0,0 +1,111 @@
+//! Encoding and decoding with an order-preserving alphabet
+//!
+//! The characters of the alphabet have strictly increasing ASCII values, so comparing two encoded
+//! strings gives the same order as comparing the bytes they encode. This makes the variant fit for
+//! keys of ordered key-value stores. Quotes, slashes, the backslash and `,.:;` are left out.
+//!
+//! The order is preserved between inputs of the same length, the usual case of fixed-size keys,
+//! and between inputs whose lengths are multiples of 4. A trailing partial group is truncated like
+//! in the RFC1924 variant, so a shorter input ending with one may sort after a longer one.
+//!
+//! # Example
+//!
+//! ```
+//! let (low, high) = (base85rs::ordered::encode(b"ab\xff"), base85rs::ordered::encode(b"ac\x00"));
+//! assert!(low < high);
+//! ```
+
+use crate::{decode_with_alphabet, encode_with_alphabet, Alphabet, DecodeError};
+
+/// The 85 characters of the order-preserving alphabet, in the order of their values
+pub const ALPHABET: &[u8; 85] =
+    b"!#$%&()*+-0123456789<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[]^_abcdefghijklmnopqrstuvwxyz{|}~";
+
+/// `encode()` turns a slice of bytes into a `String` sorting like `data`
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::ordered::encode(b"aaaaa"), "GACZXG4");
+/// ```
+#[must_use]
+pub fn encode(data: &[u8]) -> String {
+    encode_with_alphabet(data, &Alphabet::ORDERED)
+}
+
+/// `decode()` try to decode a string encoded with the order-preserving alphabet, skipping
+/// whitespaces
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet
+/// and `DecodeError::GroupOverflow` if a group exceeds `u32::MAX`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::ordered::decode("GACZXG4").unwrap(), b"aaaaa");
+/// ```
+pub fn decode(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_with_alphabet(instr, &Alphabet::ORDERED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_alphabet_sorted() {
+        assert!(ALPHABET.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn ordered_roundtrip() {
+        for len in 0..16 {
+            let data: Vec<u8> = (0..len).map(|i| i * 17).collect();
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn ordered_preserves_order() {
+        let bytes = [0u8, 1, 84, 85, 127, 254, 255];
+        for len in 1..10 {
+            let inputs: Vec<Vec<u8>> = bytes
+                .iter()
+                .flat_map(|&a| bytes.iter().map(move |&b| [a, b].repeat(5)[..len].to_vec()))
+                .collect();
+            for x in &inputs {
+                for y in &inputs {
+                    assert_eq!(x.cmp(y), encode(x).cmp(&encode(y)), "{x:?} vs {y:?}");
+                }
+            }
+        }
+
+        // Whole groups, of any length
+        let groups = [
+            vec![],
+            vec![0; 4],
+            vec![0, 0, 0, 1],
+            vec![255; 4],
+            vec![0; 8],
+            vec![1; 8],
+        ];
+        for x in &groups {
+            for y in &groups {
+                assert_eq!(x.cmp(y), encode(x).cmp(&encode(y)), "{x:?} vs {y:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn ordered_invalid() {
+        assert_eq!(
+            decode("GACZ/"),
+            Err(DecodeError::InvalidByte {
+                index: 4,
+                byte: b'/'
+            })
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Decoding of base85 data embedded in a larger input
+
+use crate::{decode_digits, incremental, to_x85, DecodeError, SalvageError};
+
+/// Why `decode_partial()` stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StopReason {
+    /// The whole input was decoded
+    EndOfInput,
+    /// A byte outside of the alphabet was found, at index `consumed` of the input
+    InvalidByte(u8),
+    /// The next decoded group doesn't fit in the output buffer
+    OutputFull,
+    /// The next group, starting at index `consumed` of the input, exceeds `u32::MAX`
+    Overflow,
+}
+
+/// The outcome of `decode_partial()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialDecode {
+    /// Number of bytes written to the output buffer
+    pub written: usize,
+    /// Number of input bytes consumed, including skipped whitespaces
+    pub consumed: usize,
+    /// Why decoding stopped
+    pub reason: StopReason,
+}
+
+/// `decode_partial()` decodes as much base85 data as possible from the start of `instr` into `out`
+///
+/// Decoding stops at the first byte outside of the alphabet, which is seen as the end of the
+/// encoded data and decoded with its trailing partial group, or when `out` is full. The caller
+/// then resumes parsing at `consumed`.
+///
+/// # Example
+///
+/// ```
+/// use base85rs::{decode_partial, StopReason};
+///
+/// let mut out = [0; 16];
+/// let partial = decode_partial(b"VPRomVE]rest", &mut out);
+/// assert_eq!(&out[..partial.written], b"aaaaa");
+/// assert_eq!(partial.consumed, 7);
+/// assert_eq!(partial.reason, StopReason::InvalidByte(b']'));
+/// ```
+#[must_use]
+pub fn decode_partial(instr: &[u8], out: &mut [u8]) -> PartialDecode {
+    let mut digits = [0; 5];
+    let mut filled = 0;
+    let mut written = 0;
+    // Input consumed by the groups written so far
+    let mut committed = 0;
+
+    let (stop, mut reason) = instr
+        .iter()
+        .enumerate()
+        .find_map(|(index, &chr)| {
+            if chr == 0x20 {
+                return None;
+            }
+            let Some(digit) = to_x85(chr) else {
+                return Some((index, StopReason::InvalidByte(chr)));
+            };
+            digits[filled] = digit;
+            filled += 1;
+            if filled < 5 {
+                return None;
+            }
+            filled = 0;
+            let Ok(bytes) = decode_digits(digits, committed) else {
+                return Some((committed, StopReason::Overflow));
+            };
+            match out.get_mut(written..written + 4) {
+                Some(group) => {
+                    group.copy_from_slice(&bytes);
+                    written += 4;
+                    committed = index + 1;
+                    None
+                }
+                None => Some((committed, StopReason::OutputFull)),
+            }
+        })
+        .unwrap_or((instr.len(), StopReason::EndOfInput));
+
+    let mut consumed = stop;
+    if !matches!(reason, StopReason::OutputFull | StopReason::Overflow) && filled > 1 {
+        digits[filled..].fill(84);
+        match (
+            decode_digits(digits, committed),
+            out.get_mut(written..written + filled - 1),
+        ) {
+            (Err(_), _) => (consumed, reason) = (committed, StopReason::Overflow),
+            (Ok(bytes), Some(tail)) => {
+                tail.copy_from_slice(&bytes[..filled - 1]);
+                written += filled - 1;
+            }
+            (Ok(_), None) => (consumed, reason) = (committed, StopReason::OutputFull),
+        }
+    }
+
+    PartialDecode {
+        written,
+        consumed,
+        reason,
+    }
+}
+
+/// `decode_salvage()` try to decode a base85 encoded &str, keeping what was decoded on failure
+///
+/// On success, this is the same as `decode()`. When a byte outside of the alphabet or an
+/// overflowing group is found, the returned error carries the bytes of the complete groups decoded
+/// before it, for instance to inspect a corrupted capture.
+///
+/// # Errors
+///
+/// Returns a `SalvageError` wrapping the `DecodeError::InvalidByte` of the first byte outside of
+/// the alphabet, or the `DecodeError::GroupOverflow` of the first group exceeding `u32::MAX`,
+/// located at its first character.
+///
+/// # Example
+///
+/// ```
+/// let err = base85rs::decode_salvage("VPRom VP]om").unwrap_err();
+/// assert_eq!(err.decoded, b"aaaa");
+/// assert_eq!(err.position, 8);
+/// ```
+pub fn decode_salvage(instr: &str) -> Result<Vec<u8>, SalvageError> {
+    let mut outdata = Vec::with_capacity(crate::decoded_len(instr.len()));
+    let mut decoder = incremental::Decoder::default();
+    let mut sink = |bytes: &[u8]| {
+        outdata.extend(bytes);
+        Ok(())
+    };
+    match decoder
+        .update(instr.as_bytes(), &mut sink)
+        .and_then(|()| decoder.finish(sink))
+    {
+        Ok(()) => Ok(outdata),
+        Err(error) => {
+            let position = match error {
+                DecodeError::InvalidByte { index, .. } | DecodeError::GroupOverflow { index } => {
+                    index
+                }
+                _ => instr.len(),
+            };
+            Err(SalvageError {
+                decoded: outdata,
+                position,
+                error,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_partial_end_of_input() {
+        let mut out = [0; 8];
+        let partial = decode_partial(b"VPRom VE ", &mut out);
+        assert_eq!(&out[..partial.written], b"aaaaa");
+        assert_eq!(partial.consumed, 9);
+        assert_eq!(partial.reason, StopReason::EndOfInput);
+    }
+
+    #[test]
+    fn decode_partial_invalid_byte() {
+        let mut out = [0; 8];
+        let partial = decode_partial(b"\"quoted\"", &mut out);
+        assert_eq!(
+            partial,
+            PartialDecode {
+                written: 0,
+                consumed: 0,
+                reason: StopReason::InvalidByte(b'"')
+            }
+        );
+    }
+
+    #[test]
+    fn decode_partial_output_full() {
+        let mut out = [0; 4];
+        let partial = decode_partial(b"VPRom VPRom", &mut out);
+        assert_eq!(&out[..partial.written], b"aaaa");
+        assert_eq!(partial.consumed, 5);
+        assert_eq!(partial.reason, StopReason::OutputFull);
+
+        // The trailing partial group doesn't fit either
+        let partial = decode_partial(b"VPRomVE]", &mut out);
+        assert_eq!(partial.written, 4);
+        assert_eq!(partial.consumed, 5);
+        assert_eq!(partial.reason, StopReason::OutputFull);
+    }
+
+    #[test]
+    fn decode_partial_overflow() {
+        let mut out = [0; 8];
+        let partial = decode_partial(b"VPRom |NsC1", &mut out);
+        assert_eq!(&out[..partial.written], b"aaaa");
+        assert_eq!(partial.consumed, 5);
+        assert_eq!(partial.reason, StopReason::Overflow);
+
+        // The trailing partial group is completed with `~` before decoding
+        let partial = decode_partial(b"VPRom|NsC", &mut out);
+        assert_eq!((partial.written, partial.consumed), (4, 5));
+        assert_eq!(partial.reason, StopReason::Overflow);
+        assert_eq!(
+            decode_partial(b"|Ns9", &mut out).reason,
+            StopReason::EndOfInput
+        );
+    }
+
+    #[test]
+    fn decode_salvage_partial_output() {
+        assert_eq!(decode_salvage("VPRomVE").unwrap(), b"aaaaa");
+
+        let err = decode_salvage("VPRomVPRomV\"Rom").unwrap_err();
+        assert_eq!(err.decoded, b"aaaaaaaa");
+        assert_eq!(err.position, 11);
+        assert_eq!(
+            err.error,
+            DecodeError::InvalidByte {
+                index: 11,
+                byte: b'"'
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "invalid byte 0x22 at index 11, after 8 decoded bytes"
+        );
+
+        let err = decode_salvage("VPRom |NsC1").unwrap_err();
+        assert_eq!(err.decoded, b"aaaa");
+        assert_eq!(err.position, 6);
+        assert_eq!(err.error, DecodeError::GroupOverflow { index: 6 });
+    }
+}
@@ -0,0 +1,461 @@
+//! Multi-threaded streaming conversion, for huge inputs
+//!
+//! A reader thread splits the input into chunks of whole groups, a pool of worker threads converts
+//! them, and the calling thread writes the converted chunks in their original order. The number of
+//! chunks in flight is bounded, memory use doesn't depend on the size of the input.
+//!
+//! # Example
+//!
+//! ```
+//! use std::num::NonZeroUsize;
+//!
+//! let workers = NonZeroUsize::new(4).unwrap();
+//! let mut encoded = Vec::new();
+//! let len = base85rs::pipeline::encode("aaaaa".as_bytes(), &mut encoded, workers).unwrap();
+//! assert_eq!((len, encoded.as_slice()), (5, b"VPRomVE".as_slice()));
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::num::NonZeroUsize;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::cancel::CancellableReader;
+use crate::{encode_u32_chunk, incremental, CancellationToken};
+
+// Bytes encoded by a worker at once, a whole number of groups
+const CHUNK_SIZE: usize = 1 << 20;
+
+// Characters, besides whitespaces, decoded by a worker at once: a whole number of groups
+const CHUNK_DIGITS: usize = CHUNK_SIZE / 4 * 5;
+
+const READ_SIZE: usize = 64 * 1024;
+
+// A chunk of input, with its index in the whole input
+struct Chunk {
+    start: usize,
+    data: Vec<u8>,
+}
+
+// Converted chunk, with the number of decoded bytes that went through the conversion
+type Converted = io::Result<(Vec<u8>, u64)>;
+
+// Sends chunks to the workers, once the writer has a slot for their result
+struct ChunkSender {
+    chunks: SyncSender<(usize, io::Result<Chunk>)>,
+    slots: Receiver<()>,
+}
+
+impl ChunkSender {
+    // Fails once the workers or the writer are done
+    fn send(&self, seq: usize, chunk: io::Result<Chunk>) -> Result<(), ()> {
+        self.slots.recv().map_err(drop)?;
+        self.chunks.send((seq, chunk)).map_err(drop)
+    }
+}
+
+/// `encode()` encodes everything read from `input` into `output`, with `workers` threads
+///
+/// The output is the same as the one of `encode()`. The number of bytes read from `input` is
+/// returned.
+///
+/// # Errors
+///
+/// Returns any I/O error from reading `input` or writing `output`.
+pub fn encode(
+    input: impl Read + Send,
+    output: impl Write,
+    workers: NonZeroUsize,
+) -> io::Result<u64> {
+    encode_cancellable(input, output, workers, &CancellationToken::new())
+}
+
+/// `encode_cancellable()` is `encode()`, stopping once `token` is cancelled
+///
+/// The reader, the workers and the writer check `token` between chunks.
+///
+/// # Errors
+///
+/// Returns any I/O error from reading `input` or writing `output`, and an error wrapping
+/// `Cancelled` if `token` is cancelled before the end.
+pub fn encode_cancellable(
+    input: impl Read + Send,
+    output: impl Write,
+    workers: NonZeroUsize,
+    token: &CancellationToken,
+) -> io::Result<u64> {
+    run(input, output, workers, token, split_bytes, |chunk| {
+        let mut buffer = [0; 5];
+        let mut out = Vec::with_capacity(crate::encoded_len(chunk.data.len()));
+        for group in chunk.data.chunks(4) {
+            let c = encode_u32_chunk(group, &mut buffer);
+            out.extend(&buffer[..=c]);
+        }
+        Ok((out, chunk.data.len() as u64))
+    })
+}
+
+/// `decode()` decodes everything read from `input` into `output`, with `workers` threads
+///
+/// Whitespaces are skipped, and the number of bytes written to `output` is returned.
+///
+/// # Errors
+///
+/// Returns any I/O error from reading `input` or writing `output`, and an
+/// `io::ErrorKind::InvalidData` error wrapping the first `DecodeError` of `input`. Error indices
+/// are relative to the whole input.
+pub fn decode(
+    input: impl Read + Send,
+    output: impl Write,
+    workers: NonZeroUsize,
+) -> io::Result<u64> {
+    decode_cancellable(input, output, workers, &CancellationToken::new())
+}
+
+/// `decode_cancellable()` is `decode()`, stopping once `token` is cancelled
+///
+/// The reader, the workers and the writer check `token` between chunks.
+///
+/// # Errors
+///
+/// Returns any I/O error from reading `input` or writing `output`, an
+/// `io::ErrorKind::InvalidData` error wrapping the first `DecodeError` of `input`, and an error
+/// wrapping `Cancelled` if `token` is cancelled before the end.
+pub fn decode_cancellable(
+    input: impl Read + Send,
+    output: impl Write,
+    workers: NonZeroUsize,
+    token: &CancellationToken,
+) -> io::Result<u64> {
+    run(input, output, workers, token, split_digits, |chunk| {
+        let mut out = Vec::with_capacity(crate::decoded_len(chunk.data.len()));
+        let mut decoder = incremental::Decoder::default();
+        decoder.set_index(chunk.start);
+        let mut sink = |bytes: &[u8]| {
+            out.extend(bytes);
+            Ok(())
+        };
+        decoder
+            .update(&chunk.data, &mut sink)
+            .and_then(|()| decoder.finish(sink))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let len = out.len() as u64;
+        Ok((out, len))
+    })
+}
+
+// Run the reader thread with `split`, the workers with `convert`, and write the converted chunks
+// in order on the calling thread, until `token` is cancelled.
+//
+// At most `workers + 1` chunks are between the reader and the writer: a slow chunk holds the
+// reader back instead of piling up the chunks converted after it.
+fn run<R: Read + Send, W: Write>(
+    input: R,
+    mut output: W,
+    workers: NonZeroUsize,
+    token: &CancellationToken,
+    split: impl FnOnce(CancellableReader<R>, &ChunkSender) + Send,
+    convert: impl Fn(Chunk) -> Converted + Sync,
+) -> io::Result<u64> {
+    let window = workers.get() + 1;
+    let (chunk_tx, chunk_rx) = sync_channel(workers.get());
+    let (converted_tx, converted_rx) = sync_channel(workers.get());
+    let (slot_tx, slot_rx) = sync_channel(window);
+    for _ in 0..window {
+        let _ = slot_tx.send(());
+    }
+    let chunk_tx = ChunkSender {
+        chunks: chunk_tx,
+        slots: slot_rx,
+    };
+    let chunk_rx = Arc::new(Mutex::new(chunk_rx));
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("pipeline", workers = workers.get()).entered();
+
+    thread::scope(|scope| {
+        let input = token.reader(input);
+        scope.spawn(move || split(input, &chunk_tx));
+        for _ in 0..workers.get() {
+            let (chunk_rx, converted_tx) = (Arc::clone(&chunk_rx), converted_tx.clone());
+            let convert = |chunk| token.check().and_then(|()| convert(chunk));
+            scope.spawn(move || work(&chunk_rx, &converted_tx, convert));
+        }
+        // Only the workers hold the channels, so that the reader stops once they all have
+        drop((chunk_rx, converted_tx));
+        let written = write_ordered(converted_rx, &slot_tx, &mut output, token);
+        // Stops the reader if it is waiting for a slot
+        drop(slot_tx);
+        written
+    })
+}
+
+// Convert chunks until the reader or the writer is done.
+fn work(
+    chunks: &Mutex<Receiver<(usize, io::Result<Chunk>)>>,
+    converted: &SyncSender<(usize, Converted)>,
+    convert: impl Fn(Chunk) -> Converted,
+) {
+    loop {
+        let next = chunks
+            .lock()
+            .map_err(drop)
+            .and_then(|rx| rx.recv().map_err(drop));
+        let Ok((seq, chunk)) = next else { return };
+        if converted.send((seq, chunk.and_then(&convert))).is_err() {
+            return;
+        }
+    }
+}
+
+// Write converted chunks in the order of their sequence numbers, stopping at the first error,
+// and free a slot of the reader for each of them. Returning drops the receiver, which stops the
+// workers and then the reader.
+fn write_ordered(
+    converted: Receiver<(usize, Converted)>,
+    slots: &SyncSender<()>,
+    output: &mut impl Write,
+    token: &CancellationToken,
+) -> io::Result<u64> {
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    let mut total = 0;
+    for (seq, result) in converted {
+        pending.insert(seq, result);
+        while let Some(result) = pending.remove(&next) {
+            token.check()?;
+            let (out, len) = match result {
+                Ok(converted) => converted,
+                Err(err) => {
+                    trace_event!(debug, chunk = next, error = %err, "conversion failed");
+                    return Err(err);
+                }
+            };
+            trace_event!(
+                trace,
+                chunk = next,
+                bytes = len,
+                written = out.len(),
+                "chunk written"
+            );
+            output.write_all(&out)?;
+            total += len;
+            next += 1;
+            // The reader may be done already
+            let _ = slots.send(());
+        }
+    }
+    output.flush()?;
+    trace_event!(debug, chunks = next, bytes = total, "conversion finished");
+    Ok(total)
+}
+
+// Fill `buffer` from `reader` as much as possible, returning the number of bytes read.
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(len) => filled += len,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+// Send chunks of `CHUNK_SIZE` bytes of `reader`, the last one possibly shorter.
+fn split_bytes(mut reader: impl Read, chunks: &ChunkSender) {
+    let mut start = 0;
+    for seq in 0.. {
+        let mut data = vec![0; CHUNK_SIZE];
+        let chunk = read_full(&mut reader, &mut data).map(|len| {
+            data.truncate(len);
+            Chunk { start, data }
+        });
+        let done = match &chunk {
+            Ok(chunk) => chunk.data.len() < CHUNK_SIZE,
+            Err(_) => true,
+        };
+        if let Ok(chunk) = &chunk {
+            start += chunk.data.len();
+        }
+        if chunks.send(seq, chunk).is_err() || done {
+            return;
+        }
+    }
+}
+
+// Send chunks of `reader` holding `CHUNK_DIGITS` characters besides whitespaces, the last one
+// possibly less, so that groups never span over two chunks.
+fn split_digits(mut reader: impl Read, chunks: &ChunkSender) {
+    let mut buffer = vec![0; READ_SIZE];
+    let mut chunk = Chunk {
+        start: 0,
+        data: Vec::new(),
+    };
+    let mut digits = 0;
+    let mut seq = 0;
+    loop {
+        let len = match read_full(&mut reader, &mut buffer) {
+            Ok(len) => len,
+            Err(err) => {
+                let _ = chunks.send(seq, Err(err));
+                return;
+            }
+        };
+        let mut rest = &buffer[..len];
+        while let Some(end) = rest
+            .iter()
+            .enumerate()
+            .filter(|&(_, &chr)| chr != 0x20)
+            .nth(CHUNK_DIGITS - digits - 1)
+            .map(|(index, _)| index + 1)
+        {
+            chunk.data.extend(&rest[..end]);
+            rest = &rest[end..];
+            let start = chunk.start + chunk.data.len();
+            let full = std::mem::replace(
+                &mut chunk,
+                Chunk {
+                    start,
+                    data: Vec::new(),
+                },
+            );
+            if chunks.send(seq, Ok(full)).is_err() {
+                return;
+            }
+            seq += 1;
+            digits = 0;
+        }
+        digits += rest.iter().filter(|&&chr| chr != 0x20).count();
+        chunk.data.extend(rest);
+        if len < buffer.len() {
+            let _ = chunks.send(seq, Ok(chunk));
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DecodeError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn workers(count: usize) -> NonZeroUsize {
+        NonZeroUsize::new(count).unwrap()
+    }
+
+    #[test]
+    fn pipeline_roundtrip() {
+        let data: Vec<u8> = (0..251).cycle().take(3 * CHUNK_SIZE + 7).collect();
+        for count in [1, 3] {
+            let mut encoded = Vec::new();
+            assert_eq!(
+                encode(data.as_slice(), &mut encoded, workers(count)).unwrap(),
+                data.len() as u64
+            );
+            assert_eq!(encoded, crate::encode(&data).as_bytes());
+
+            let mut decoded = Vec::new();
+            assert_eq!(
+                decode(encoded.as_slice(), &mut decoded, workers(count)).unwrap(),
+                data.len() as u64
+            );
+            assert_eq!(decoded, data);
+        }
+    }
+
+    #[test]
+    fn pipeline_decode_whitespaces() {
+        let data: Vec<u8> = (0..7).cycle().take(CHUNK_SIZE + 5).collect();
+        let spaced: Vec<u8> = crate::encode(&data)
+            .bytes()
+            .flat_map(|chr| [chr, b' '])
+            .collect();
+        let mut decoded = Vec::new();
+        decode(spaced.as_slice(), &mut decoded, workers(2)).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn pipeline_decode_error_index() {
+        let mut encoded = crate::encode(&vec![0; 2 * CHUNK_SIZE]).into_bytes();
+        let index = CHUNK_DIGITS + 12;
+        encoded[index] = b']';
+        encoded[2 * CHUNK_DIGITS - 1] = b']';
+        let err = decode(encoded.as_slice(), io::sink(), workers(4)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidByte { index, byte: b']' })
+        );
+    }
+
+    #[test]
+    fn pipeline_cancel() {
+        // Cancels its token on the first write
+        struct Cancelling<'a>(&'a CancellationToken, usize);
+        impl Write for Cancelling<'_> {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.1 += buf.len();
+                self.0.cancel();
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let token = CancellationToken::new();
+        let data = vec![0; 4 * CHUNK_SIZE];
+        let mut output = Cancelling(&token, 0);
+        let err = encode_cancellable(data.as_slice(), &mut output, workers(2), &token).unwrap_err();
+        assert!(CancellationToken::is_cancellation(&err));
+        assert_eq!(output.1, CHUNK_DIGITS);
+
+        let err = decode_cancellable(io::repeat(b'0'), io::sink(), workers(2), &token).unwrap_err();
+        assert!(CancellationToken::is_cancellation(&err));
+    }
+
+    #[test]
+    fn pipeline_slow_chunk() {
+        // Counts the bytes read from it
+        struct Counting<'a, R>(&'a AtomicUsize, R);
+        impl<R: Read> Read for Counting<'_, R> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let len = self.1.read(buf)?;
+                self.0.fetch_add(len, Ordering::Relaxed);
+                Ok(len)
+            }
+        }
+
+        // The first chunk is converted last, the reader waits for it instead of reading ahead
+        let read = AtomicUsize::new(0);
+        let read_before_first = AtomicUsize::new(0);
+        let input = Counting(&read, io::repeat(0).take(16 * CHUNK_SIZE as u64));
+        let convert = |chunk: Chunk| {
+            if chunk.start == 0 {
+                thread::sleep(Duration::from_millis(200));
+                read_before_first.store(read.load(Ordering::Relaxed), Ordering::Relaxed);
+            }
+            Ok((Vec::new(), chunk.data.len() as u64))
+        };
+        let token = CancellationToken::new();
+        let len = run(input, io::sink(), workers(2), &token, split_bytes, convert).unwrap();
+        assert_eq!(len, 16 * CHUNK_SIZE as u64);
+        // The window of 3 chunks, and the one read while waiting for a slot
+        assert!(read_before_first.into_inner() <= 4 * CHUNK_SIZE);
+    }
+
+    #[test]
+    fn pipeline_empty() {
+        let mut out = Vec::new();
+        assert_eq!(encode(io::empty(), &mut out, workers(2)).unwrap(), 0);
+        assert_eq!(decode(io::empty(), &mut out, workers(2)).unwrap(), 0);
+        assert!(out.is_empty());
+    }
+}
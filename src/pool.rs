@@ -0,0 +1,152 @@
+//! Reuse of the scratch buffers of the streaming encoder and decoder
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+/// `BufferPool` keeps the buffers of finished encoders and decoders, to hand them over to new ones
+///
+/// Sharing a pool between the `EncoderWriter` and `DecoderReader` created with `with_pool()`
+/// spares allocating their buffers for every conversion, in servers running many short ones. A
+/// pool can be shared across threads, and keeps at most `max_idle` buffers.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Write;
+/// use std::sync::Arc;
+///
+/// let pool = Arc::new(base85rs::BufferPool::new(16));
+/// for _ in 0..3 {
+///     let mut encoder = base85rs::EncoderWriter::with_pool(Vec::new(), &pool);
+///     encoder.write_all(b"aaaaa").unwrap();
+///     assert_eq!(encoder.finish().unwrap(), b"VPRomVE");
+/// }
+/// assert_eq!(pool.idle(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    max_idle: usize,
+}
+
+impl BufferPool {
+    /// `new()` creates an empty pool, keeping up to `max_idle` buffers
+    #[must_use]
+    pub fn new(max_idle: usize) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::new()),
+            max_idle,
+        }
+    }
+
+    /// `get()` returns an empty buffer, reusing the allocation of a released one if any
+    pub fn get(&self) -> Vec<u8> {
+        self.buffers
+            .lock()
+            .ok()
+            .and_then(|mut buffers| buffers.pop())
+            .unwrap_or_default()
+    }
+
+    /// `put()` releases `buffer` into the pool, or drops it if the pool is full
+    ///
+    /// The buffer is cleared, and wiped with the `zeroize` feature.
+    pub fn put(&self, mut buffer: Vec<u8>) {
+        #[cfg(feature = "zeroize")]
+        ::zeroize::Zeroize::zeroize(&mut buffer);
+        buffer.clear();
+        if let Ok(mut buffers) = self.buffers.lock() {
+            if buffers.len() < self.max_idle {
+                buffers.push(buffer);
+            }
+        }
+    }
+
+    /// `idle()` returns the number of buffers kept by the pool
+    #[must_use]
+    pub fn idle(&self) -> usize {
+        self.buffers.lock().map_or(0, |buffers| buffers.len())
+    }
+}
+
+// Buffer going back to its pool, if any, when dropped
+#[derive(Debug, Default)]
+pub(crate) struct Buffer {
+    data: Vec<u8>,
+    pool: Option<Arc<BufferPool>>,
+}
+
+impl Buffer {
+    pub(crate) fn new(pool: Option<&Arc<BufferPool>>) -> Self {
+        Self {
+            data: pool.map(|pool| pool.get()).unwrap_or_default(),
+            pool: pool.cloned(),
+        }
+    }
+
+    // Hand the data over to the pool, which wipes it, or wipe it in place with the `zeroize` feature
+    fn release(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.put(std::mem::take(&mut self.data));
+        } else {
+            #[cfg(feature = "zeroize")]
+            ::zeroize::Zeroize::zeroize(&mut self.data);
+        }
+    }
+}
+
+impl Deref for Buffer {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.data
+    }
+}
+
+impl DerefMut for Buffer {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.data
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_reuses_allocations() {
+        let pool = Arc::new(BufferPool::new(1));
+        let mut buffer = Buffer::new(Some(&pool));
+        buffer.extend([1; 100]);
+        let other = Buffer::new(Some(&pool));
+        drop(buffer);
+        drop(other);
+        // The pool is full after the first buffer
+        assert_eq!(pool.idle(), 1);
+
+        let reused = pool.get();
+        assert!(reused.is_empty());
+        assert!(reused.capacity() >= 100);
+        assert_eq!(pool.idle(), 0);
+
+        drop(Buffer::new(None));
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn unpooled_buffer_wiped() {
+        let mut buffer = Buffer::new(None);
+        buffer.extend(b"secret");
+        buffer.release();
+        // Cleared in place, keeping the allocation zeroized
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 6);
+    }
+}
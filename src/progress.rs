@@ -0,0 +1,138 @@
+//! Progress reporting of long-running streaming conversions
+
+use std::io::{self, Read};
+use std::num::NonZeroU64;
+
+/// `Progress` is what a `ProgressReader` reports to its callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes read so far
+    pub processed: u64,
+    /// Bytes to read in total, when known
+    pub total: Option<u64>,
+}
+
+/// `ProgressReader` calls back with the number of bytes read from an inner reader, every
+/// `interval` bytes and once more at its end
+///
+/// Wrapping the input of `copy_encode()`, `transcode::transcode()` or `pipeline::encode()` tracks
+/// conversions too long to wait for blindly, to drive a progress bar or service metrics. The
+/// callback runs on the thread reading: the pipeline reader thread for the `pipeline` module.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Read;
+/// use std::num::NonZeroU64;
+///
+/// // Reads of 4, 4 and 2 bytes
+/// let input = b"aaaa".chain(&b"aaaa"[..]).chain(&b"aa"[..]);
+/// let mut reports = Vec::new();
+/// let interval = NonZeroU64::new(4).unwrap();
+/// let mut reader = base85rs::ProgressReader::new(input, interval, |progress| {
+///     reports.push(progress.processed);
+/// })
+/// .with_total(10);
+/// base85rs::copy_encode(&mut reader, &mut std::io::sink()).unwrap();
+/// assert_eq!(reports, [4, 8, 10]);
+/// ```
+#[derive(Debug)]
+pub struct ProgressReader<R, F> {
+    inner: R,
+    callback: F,
+    interval: NonZeroU64,
+    processed: u64,
+    total: Option<u64>,
+    // Bytes read when the callback is due next, `None` once the end was reported
+    next: Option<u64>,
+    // Bytes read when the callback was last called
+    reported: u64,
+}
+
+impl<R: Read, F: FnMut(Progress)> ProgressReader<R, F> {
+    /// `new()` wraps `inner`, calling `callback` every `interval` bytes read from it
+    pub fn new(inner: R, interval: NonZeroU64, callback: F) -> Self {
+        Self {
+            inner,
+            callback,
+            interval,
+            processed: 0,
+            total: None,
+            next: Some(interval.get()),
+            reported: 0,
+        }
+    }
+
+    /// `with_total()` sets the number of bytes `inner` holds, reported along with the progress
+    #[must_use]
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// `processed()` returns the number of bytes read so far
+    #[must_use]
+    pub fn processed(&self) -> u64 {
+        self.processed
+    }
+
+    /// `into_inner()` gives the inner reader back
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn report(&mut self) {
+        self.reported = self.processed;
+        (self.callback)(Progress {
+            processed: self.processed,
+            total: self.total,
+        });
+    }
+}
+
+impl<R: Read, F: FnMut(Progress)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.processed += read as u64;
+        match self.next {
+            // The end, unless its count has just been reported
+            Some(_) if read == 0 && !buf.is_empty() => {
+                self.next = None;
+                if self.reported != self.processed {
+                    self.report();
+                }
+            }
+            Some(next) if self.processed >= next => {
+                let interval = self.interval.get();
+                self.next = Some((self.processed / interval + 1) * interval);
+                self.report();
+            }
+            _ => {}
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{copy_encode, encode};
+
+    #[test]
+    fn progress_intervals() {
+        let data = vec![0; 10_000];
+        let mut reports = Vec::new();
+        let interval = NonZeroU64::new(3_000).unwrap();
+        let mut reader = ProgressReader::new(data.as_slice(), interval, |progress| {
+            reports.push(progress);
+        });
+        let mut encoded = Vec::new();
+        // Reads of 8 KiB skip past several intervals at once
+        copy_encode(&mut reader, &mut encoded).unwrap();
+        assert_eq!(reader.processed(), 10_000);
+        assert_eq!(encoded, encode(&data).as_bytes());
+        let processed: Vec<u64> = reports.iter().map(|progress| progress.processed).collect();
+        assert_eq!(processed, [8_192, 10_000]);
+        assert!(reports.iter().all(|progress| progress.total.is_none()));
+    }
+}
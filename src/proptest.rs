@@ -0,0 +1,94 @@
+//! `proptest` strategies, to write roundtrip and robustness properties of code embedding base85
+//!
+//! # Example
+//!
+//! ```
+//! use base85rs::proptest::{corrupted, encoded};
+//! use proptest::prelude::*;
+//!
+//! proptest!(|((encoded, data) in encoded())| {
+//!     prop_assert_eq!(base85rs::decode(&encoded), Some(data));
+//! });
+//! proptest!(|((corrupted, index) in corrupted())| {
+//!     let err = base85rs::decode_with_limit(&corrupted, usize::MAX).unwrap_err();
+//!     let byte = corrupted.as_bytes()[index];
+//!     prop_assert_eq!(err, base85rs::DecodeError::InvalidByte { index, byte });
+//! });
+//! ```
+
+use ::proptest::prelude::*;
+
+use crate::encode;
+
+// ASCII characters which are neither part of the alphabet nor skipped as whitespaces
+const INVALID_CHARS: &[char] = &[
+    '"', '\'', ',', '.', '/', ':', '[', '\\', ']', '\t', '\n', '\0',
+];
+
+/// `payload()` generates random byte payloads, up to 1 KiB
+pub fn payload() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), 0..=1024)
+}
+
+/// `encoded()` generates valid encoded strings, with spaces inserted at random positions, along
+/// with the data they decode to
+pub fn encoded() -> impl Strategy<Value = (String, Vec<u8>)> {
+    payload().prop_flat_map(|data| {
+        let encoded = encode(&data);
+        let len = encoded.len();
+        (
+            Just(encoded),
+            Just(data),
+            prop::collection::vec(0..=len, 0..4),
+        )
+            .prop_map(|(mut encoded, data, mut spaces)| {
+                spaces.sort_unstable();
+                for position in spaces.into_iter().rev() {
+                    encoded.insert(position, ' ');
+                }
+                (encoded, data)
+            })
+    })
+}
+
+/// `corrupted()` generates near-valid encoded strings, where a single character was replaced by
+/// one outside of the alphabet, along with the index of this character
+pub fn corrupted() -> impl Strategy<Value = (String, usize)> {
+    prop::collection::vec(any::<u8>(), 1..=1024).prop_flat_map(|data| {
+        let encoded = encode(&data);
+        let len = encoded.len();
+        (Just(encoded), 0..len, prop::sample::select(INVALID_CHARS)).prop_map(
+            |(encoded, index, invalid)| {
+                let mut corrupted = encoded.into_bytes();
+                // Both characters are ASCII, the string stays valid
+                corrupted[index] = u8::try_from(invalid).unwrap_or_default();
+                (String::from_utf8(corrupted).unwrap_or_default(), index)
+            },
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, decode_with_limit, DecodeError};
+
+    proptest! {
+        #[test]
+        fn encoded_roundtrip((encoded, data) in encoded()) {
+            prop_assert_eq!(decode(&encoded), Some(data));
+        }
+
+        #[test]
+        fn corrupted_invalid_byte((corrupted, index) in corrupted()) {
+            let err = decode_with_limit(&corrupted, usize::MAX).unwrap_err();
+            let byte = corrupted.as_bytes()[index];
+            prop_assert_eq!(err, DecodeError::InvalidByte { index, byte });
+        }
+
+        #[test]
+        fn payload_roundtrip(data in payload()) {
+            prop_assert_eq!(decode(encode(&data)), Some(data));
+        }
+    }
+}
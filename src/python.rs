@@ -0,0 +1,72 @@
+//! `PyO3` bindings, exposing the codec as a `base85rs` Python module
+//!
+//! ```python
+//! import base85rs
+//!
+//! base85rs.b85_encode(b"a")  # b"VE"
+//! base85rs.b85_decode("VE")  # b"a"
+//! ```
+//!
+//! As with Python's `base64.b85encode()` / `base64.b85decode()`, encoded data is returned as
+//! `bytes` and decoding accepts either `bytes` or `str`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::{decode_vec, encode};
+
+/// Encode `bytes` into base85 `bytes`
+#[pyfunction]
+fn b85_encode<'py>(py: Python<'py>, data: &[u8]) -> Bound<'py, PyBytes> {
+    PyBytes::new(py, encode(data).as_bytes())
+}
+
+/// Decode base85 `bytes` or `str` into `bytes`, raising `ValueError` on invalid input
+#[pyfunction]
+fn b85_decode<'py>(py: Python<'py>, data: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyBytes>> {
+    let decoded = match data.cast::<PyBytes>() {
+        Ok(bytes) => decode_vec(bytes.as_bytes()),
+        Err(_) => decode_vec(data.extract::<String>()?.as_bytes()),
+    };
+    decoded
+        .map(|outdata| PyBytes::new(py, &outdata))
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+#[pymodule]
+fn base85rs(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(b85_encode, module)?)?;
+    module.add_function(wrap_pyfunction!(b85_decode, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn python_roundtrip() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "base85rs").unwrap();
+            base85rs(&module).unwrap();
+
+            let encoded = module
+                .getattr("b85_encode")
+                .unwrap()
+                .call1((b"aaaaa",))
+                .unwrap();
+            assert_eq!(encoded.extract::<Vec<u8>>().unwrap(), b"VPRomVE");
+
+            let decode = module.getattr("b85_decode").unwrap();
+            let decoded = decode.call1((encoded,)).unwrap();
+            assert_eq!(decoded.extract::<Vec<u8>>().unwrap(), b"aaaaa");
+            let decoded = decode.call1(("VPRomVE",)).unwrap();
+            assert_eq!(decoded.extract::<Vec<u8>>().unwrap(), b"aaaaa");
+
+            let err = decode.call1(("VE]",)).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+}
@@ -0,0 +1,267 @@
+//! Random access to the decoded bytes of an encoded input
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use crate::incremental;
+
+// Groups between two checkpoints of the index
+const STRIDE: u64 = 1024;
+
+const READ_SIZE: usize = 8 * 1024;
+
+/// `RandomAccessDecoder` decodes any byte range of an encoded input, without decoding what comes
+/// before it
+///
+/// Every group of 5 characters decodes to 4 bytes, so the group holding a decoded byte is known
+/// from its offset. Spaces can appear anywhere in the input though: `new()` reads it once, without
+/// decoding it, to record where every 1024th group starts. A range is then decoded from the
+/// nearest recorded group, going through at most 1023 more groups than the range covers.
+///
+/// Any `Read + Seek` input works, an encoded string through `io::Cursor`.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// let mut decoder = base85rs::RandomAccessDecoder::new(Cursor::new("VPRom VPRom")).unwrap();
+/// assert_eq!(decoder.len(), 8);
+/// assert_eq!(decoder.decode_range(3..6).unwrap(), b"aaa");
+/// ```
+#[derive(Debug)]
+pub struct RandomAccessDecoder<R> {
+    inner: R,
+    // Offsets in `inner` of the groups 0, STRIDE, 2 * STRIDE...
+    checkpoints: Vec<u64>,
+    len: u64,
+}
+
+// Read into `buffer`, retrying on interruptions.
+fn read_some(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match reader.read(buffer) {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            result => return result,
+        }
+    }
+}
+
+// Position in `chunk` of its `n`th character besides spaces
+fn nth_digit(chunk: &[u8], n: usize) -> Option<usize> {
+    chunk
+        .iter()
+        .enumerate()
+        .filter(|&(_, &chr)| chr != 0x20)
+        .nth(n)
+        .map(|(i, _)| i)
+}
+
+fn digit_count(chunk: &[u8]) -> usize {
+    chunk.iter().filter(|&&chr| chr != 0x20).count()
+}
+
+impl<R: Read + Seek> RandomAccessDecoder<R> {
+    /// `new()` indexes the encoded input `inner`, from its start
+    ///
+    /// Characters outside of the alphabet aren't checked here, but by the `decode_range()` calls
+    /// going through them.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from seeking or reading `inner`.
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        inner.rewind()?;
+        let mut checkpoints = Vec::new();
+        let mut digits = 0_u64;
+        let mut offset = 0_u64;
+        let mut buffer = vec![0; READ_SIZE];
+        loop {
+            let len = read_some(&mut inner, &mut buffer)?;
+            if len == 0 {
+                break;
+            }
+            for (i, _) in buffer[..len]
+                .iter()
+                .enumerate()
+                .filter(|&(_, &chr)| chr != 0x20)
+            {
+                if digits % (STRIDE * 5) == 0 {
+                    checkpoints.push(offset + i as u64);
+                }
+                digits += 1;
+            }
+            offset += len as u64;
+        }
+        Ok(Self {
+            inner,
+            checkpoints,
+            len: digits / 5 * 4 + (digits % 5).saturating_sub(1),
+        })
+    }
+
+    /// `len()` returns the number of decoded bytes
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// `is_empty()` tells whether the input decodes to no bytes at all
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// `decode_range()` decodes the bytes of `range`, offsets in the decoded data
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::ErrorKind::InvalidInput` error if `range` isn't within `len()`, any I/O
+    /// error from seeking or reading the input, and an `io::ErrorKind::InvalidData` error wrapping
+    /// the `DecodeError` of an invalid character of the groups covering `range`. Error indices are
+    /// relative to the whole input.
+    pub fn decode_range(&mut self, range: Range<u64>) -> io::Result<Vec<u8>> {
+        if range.start > range.end || range.end > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "range out of the decoded bytes",
+            ));
+        }
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let first_group = range.start / 4;
+        let needed = usize::try_from(range.end - first_group * 4)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "range too large"))?;
+        // `range.start` is decoded from digits of the input, so the checkpoint exists
+        let mut offset = self.checkpoints[usize::try_from(first_group / STRIDE).unwrap_or(0)];
+        self.inner.seek(SeekFrom::Start(offset))?;
+
+        let mut buffer = vec![0; READ_SIZE];
+        let mut out = Vec::with_capacity(needed);
+        let mut decoder = incremental::Decoder::default();
+        decoder.set_index(usize::try_from(offset).unwrap_or(usize::MAX));
+        let mut skip = (first_group % STRIDE) * 5;
+        // Digits of the groups covering `range`, the last one possibly partial
+        let mut remaining = needed.div_ceil(4) * 5;
+        while remaining > 0 {
+            let len = read_some(&mut self.inner, &mut buffer)?;
+            if len == 0 {
+                break;
+            }
+            let mut chunk = &buffer[..len];
+            if skip > 0 {
+                // Position of the first digit of `first_group`, if it is in this chunk
+                let Some(first) = nth_digit(chunk, usize::try_from(skip).unwrap_or(usize::MAX))
+                else {
+                    skip -= digit_count(chunk) as u64;
+                    offset += len as u64;
+                    continue;
+                };
+                skip = 0;
+                chunk = &chunk[first..];
+                offset += first as u64;
+                decoder.set_index(usize::try_from(offset).unwrap_or(usize::MAX));
+            }
+            // Stop after the last digit needed, the following ones may be invalid
+            if let Some(last) = nth_digit(chunk, remaining - 1) {
+                chunk = &chunk[..=last];
+                remaining = 0;
+            } else {
+                remaining -= digit_count(chunk);
+            }
+            decoder
+                .update(chunk, |bytes| {
+                    out.extend(bytes);
+                    Ok(())
+                })
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        }
+        if out.len() < needed {
+            decoder
+                .finish(|bytes| {
+                    out.extend(bytes);
+                    Ok(())
+                })
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        }
+        if out.len() < needed {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        out.truncate(needed);
+        out.drain(..usize::try_from(range.start % 4).unwrap_or(0));
+        Ok(out)
+    }
+
+    /// `into_inner()` gives the input back
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, DecodeError};
+    use std::io::Cursor;
+
+    #[test]
+    fn random_access_ranges() {
+        let data: Vec<u8> = (0..10_003_u32).map(|i| (i % 251) as u8).collect();
+        let encoded = encode(&data);
+        let spaced: String = encoded
+            .chars()
+            .enumerate()
+            .flat_map(|(i, chr)| [chr].into_iter().chain((i % 7 == 0).then_some(' ')))
+            .collect();
+        for input in [encoded, spaced] {
+            let mut decoder = RandomAccessDecoder::new(Cursor::new(input)).unwrap();
+            assert_eq!(decoder.len(), data.len() as u64);
+            for (start, end) in [
+                (0, 0),
+                (0, 1),
+                (3, 9),
+                (4095, 4097),
+                (5000, 10_003),
+                (10_002, 10_003),
+            ] {
+                assert_eq!(
+                    decoder.decode_range(start as u64..end as u64).unwrap(),
+                    &data[start..end]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn random_access_errors() {
+        let mut encoded = encode(&[0; 8192]).into_bytes();
+        encoded[9000] = b']';
+        let mut decoder = RandomAccessDecoder::new(Cursor::new(encoded)).unwrap();
+        // Groups before and after the invalid character are still readable
+        assert_eq!(decoder.decode_range(0..7196).unwrap(), vec![0; 7196]);
+        assert_eq!(decoder.decode_range(7204..8192).unwrap(), vec![0; 988]);
+        let err = decoder.decode_range(7000..7201).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidByte {
+                index: 9000,
+                byte: b']'
+            })
+        );
+        assert_eq!(
+            decoder.decode_range(8000..8193).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn random_access_empty() {
+        let mut decoder = RandomAccessDecoder::new(Cursor::new(" V ")).unwrap();
+        assert!(decoder.is_empty());
+        assert_eq!(decoder.decode_range(0..0).unwrap(), b"");
+    }
+}
@@ -0,0 +1,150 @@
+//! Decoding of inputs made of several base85 records separated by a delimiter
+
+use std::io::{self, BufRead};
+
+use crate::{decode_vec, DecodeError};
+
+/// `decode_records()` decodes every base85 record of `instr`, separated by `delimiter`
+///
+/// Empty records, such as the one following a trailing delimiter, are skipped. Errors report
+/// indices in the whole input, and don't stop the iteration over the following records.
+///
+/// # Panics
+///
+/// Panics if `delimiter` is empty.
+///
+/// # Example
+///
+/// ```
+/// let records: Vec<_> = base85rs::decode_records("VE\n\nVPRomVE\n\n", "\n\n").collect();
+/// assert_eq!(records, [Ok(b"a".to_vec()), Ok(b"aaaaa".to_vec())]);
+/// ```
+pub fn decode_records<'a>(
+    instr: &'a str,
+    delimiter: &'a str,
+) -> impl Iterator<Item = Result<Vec<u8>, DecodeError>> + 'a {
+    assert!(!delimiter.is_empty(), "delimiter must not be empty");
+
+    instr
+        .split(delimiter)
+        .scan(0, move |offset, record| {
+            let start = *offset;
+            *offset += record.len() + delimiter.len();
+            Some((start, record))
+        })
+        .filter(|(_, record)| !record.is_empty())
+        .map(|(start, record)| decode_vec(record.as_bytes()).map_err(|err| err.offset_by(start)))
+}
+
+/// `decode_lines()` decodes every line of `reader` as an independent base85 record
+///
+/// Both `\n` and `\r\n` line endings are accepted, and blank lines are skipped. Decoding errors
+/// report indices in the whole input and don't stop the iteration, while the iteration ends
+/// after an I/O error.
+///
+/// # Errors
+///
+/// Every item is either an I/O error from `reader`, or an `io::ErrorKind::InvalidData` error
+/// wrapping the `DecodeError` of an invalid line.
+///
+/// # Example
+///
+/// ```
+/// let input = "VE\r\n\nVPRomVE\n".as_bytes();
+/// let records: Vec<_> = base85rs::decode_lines(input).map(Result::unwrap).collect();
+/// assert_eq!(records, [b"a".to_vec(), b"aaaaa".to_vec()]);
+/// ```
+pub fn decode_lines<R: BufRead>(mut reader: R) -> impl Iterator<Item = io::Result<Vec<u8>>> {
+    let mut line = Vec::new();
+    let mut offset = 0;
+    let mut failed = false;
+
+    std::iter::from_fn(move || {
+        while !failed {
+            line.clear();
+            let start = offset;
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => return None,
+                Ok(len) => offset += len,
+                Err(err) => {
+                    failed = true;
+                    return Some(Err(err));
+                }
+            }
+            let record = line.strip_suffix(b"\n").unwrap_or(&line);
+            let record = record.strip_suffix(b"\r").unwrap_or(record);
+            if record.iter().all(|&byte| byte == b' ') {
+                continue;
+            }
+            return Some(
+                decode_vec(record).map_err(|err| {
+                    io::Error::new(io::ErrorKind::InvalidData, err.offset_by(start))
+                }),
+            );
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_records_custom_delimiter() {
+        let records: Vec<_> = decode_records("aBpmEWo~R`b8`|V_|k>Yh`6{WpV", "|").collect();
+        // `|` is part of the alphabet, the second record is split in two
+        assert_eq!(records.len(), 3);
+
+        let records: Vec<_> = decode_records("aBpmEWo~R`b8`,V_|k>Yh`6{WpV,", ",")
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(records, ["pollenless".as_bytes(), b"cavekeeper"]);
+    }
+
+    #[test]
+    fn decode_records_empty() {
+        assert_eq!(decode_records("", "\n").count(), 0);
+        assert_eq!(decode_records("\n\n\n", "\n").count(), 0);
+    }
+
+    #[test]
+    fn decode_records_error_index() {
+        let records: Vec<_> = decode_records("VE\nV]\nVE", "\n").collect();
+        assert_eq!(
+            records,
+            [
+                Ok(b"a".to_vec()),
+                Err(DecodeError::InvalidByte {
+                    index: 4,
+                    byte: b']'
+                }),
+                Ok(b"a".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_lines_endings() {
+        let input = "VE\nVPRomVE\r\n\n  \r\nVE".as_bytes();
+        let records: Vec<_> = decode_lines(input).map(Result::unwrap).collect();
+        assert_eq!(records, [b"a".to_vec(), b"aaaaa".to_vec(), b"a".to_vec()]);
+        assert_eq!(decode_lines("".as_bytes()).count(), 0);
+    }
+
+    #[test]
+    fn decode_lines_error_index() {
+        let records: Vec<_> = decode_lines("VE\r\nV]\nVE\n".as_bytes()).collect();
+        assert_eq!(records.len(), 3);
+        let err = records[1].as_ref().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidByte {
+                index: 5,
+                byte: b']'
+            })
+        );
+        assert_eq!(records[2].as_ref().unwrap(), b"a");
+    }
+}
@@ -0,0 +1,373 @@
+//! `serde_with` adapter, serializing bytes as base85 strings
+//!
+//! # Example
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use serde_with::serde_as;
+//!
+//! #[serde_as]
+//! #[derive(Serialize, Deserialize)]
+//! struct Blob {
+//!     #[serde_as(as = "base85rs::serde_with::Base85")]
+//!     data: Vec<u8>,
+//! }
+//!
+//! let json = serde_json::to_string(&Blob { data: b"aaaaa".to_vec() }).unwrap();
+//! assert_eq!(json, r#"{"data":"VPRomVE"}"#);
+//! ```
+//!
+//! Human-readable formats, such as JSON or YAML, carry a base85 string, while binary formats such
+//! as bincode or CBOR carry the raw bytes, so the same struct can go through both kinds of
+//! formats. Deserializing from a human-readable format also accepts a sequence of bytes, such as
+//! a JSON array of numbers.
+//!
+//! `Base85String` and `Base85Bytes` force either representation whatever the format, for
+//! payloads that must stay readable in a binary format, or be compact in a text one:
+//!
+//! ```
+//! use serde::Serialize;
+//! use serde_with::serde_as;
+//!
+//! #[serde_as]
+//! #[derive(Serialize)]
+//! struct Blob {
+//!     #[serde_as(as = "base85rs::serde_with::Base85Bytes")]
+//!     data: Vec<u8>,
+//! }
+//!
+//! let json = serde_json::to_string(&Blob { data: vec![1, 2] }).unwrap();
+//! assert_eq!(json, r#"{"data":[1,2]}"#);
+//! ```
+//!
+//! With the `schemars` feature, `Base85` also describes its fields in the JSON schemas derived
+//! with `schemars::JsonSchema`: a string of the RFC1924 alphabet, matching `SCHEMA_PATTERN`.
+
+use std::fmt;
+
+use ::serde_with::{DeserializeAs, SerializeAs};
+use serde::de::{Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::Serializer;
+
+use crate::{decode_vec, encode};
+
+/// Regular expression matching the strings produced by `Base85`, used by its JSON schema
+///
+/// Groups of 5 characters of the RFC1924 alphabet, the last one possibly holding 2 to 4 of them
+/// only.
+pub const SCHEMA_PATTERN: &str =
+    r"^(?:[0-9A-Za-z!#$%&()*+\-;<=>?@^_`{|}~]{5})*(?:[0-9A-Za-z!#$%&()*+\-;<=>?@^_`{|}~]{2,4})?$";
+
+/// Base85 (RFC1924) representation of bytes, to use with `#[serde_as(as = "Base85")]`
+///
+/// A base85 string in human-readable formats, the raw bytes in binary ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Base85;
+
+/// `Base85` always serialized as a base85 string, binary formats included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Base85String;
+
+/// `Base85` always serialized as raw bytes, human-readable formats included
+///
+/// Human-readable formats write them as a sequence of numbers, such as a JSON array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Base85Bytes;
+
+fn serialize_string<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&encode(bytes))
+}
+
+fn serialize_bytes<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(bytes)
+}
+
+impl<T: AsRef<[u8]>> SerializeAs<T> for Base85 {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serialize_string(source.as_ref(), serializer)
+        } else {
+            serialize_bytes(source.as_ref(), serializer)
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> SerializeAs<T> for Base85String {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_string(source.as_ref(), serializer)
+    }
+}
+
+impl<T: AsRef<[u8]>> SerializeAs<T> for Base85Bytes {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(source.as_ref(), serializer)
+    }
+}
+
+// Visitor accepting a base85 string, raw bytes or a sequence of bytes
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a base85 string or a byte sequence")
+    }
+
+    fn visit_str<E: Error>(self, encoded: &str) -> Result<Vec<u8>, E> {
+        decode_vec(encoded.as_bytes()).map_err(Error::custom)
+    }
+
+    fn visit_bytes<E: Error>(self, bytes: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(bytes.to_vec())
+    }
+
+    fn visit_byte_buf<E: Error>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(bytes)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+impl<'de> DeserializeAs<'de, Vec<u8>> for Base85 {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        // Binary formats may not describe their content, such as bincode: ask for the bytes
+        // `serialize_as()` wrote
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BytesVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+}
+
+impl<'de> DeserializeAs<'de, Vec<u8>> for Base85String {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        deserializer.deserialize_str(BytesVisitor)
+    }
+}
+
+impl<'de> DeserializeAs<'de, Vec<u8>> for Base85Bytes {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+// Fixed-size arrays, deserialized as `Vec<u8>` and checked for their length
+macro_rules! deserialize_array {
+    ($($adapter:ty),*) => {$(
+        impl<'de, const N: usize> DeserializeAs<'de, [u8; N]> for $adapter {
+            fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<[u8; N], D::Error> {
+                let decoded: Vec<u8> = Self::deserialize_as(deserializer)?;
+                let len = decoded.len();
+                decoded
+                    .try_into()
+                    .map_err(|_| Error::invalid_length(len, &format!("{N} bytes").as_str()))
+            }
+        }
+    )*};
+}
+
+deserialize_array!(Base85, Base85String, Base85Bytes);
+
+#[cfg(feature = "schemars")]
+impl<T> ::serde_with::schemars_1::JsonSchemaAs<T> for Base85String {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Base85".into()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        "base85rs::serde_with::Base85".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        <Base85 as ::serde_with::schemars_1::JsonSchemaAs<T>>::json_schema(generator)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl<T> ::serde_with::schemars_1::JsonSchemaAs<T> for Base85 {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Base85".into()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        "base85rs::serde_with::Base85".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "contentEncoding": "base85",
+            "pattern": SCHEMA_PATTERN,
+        })
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl<T> ::serde_with::schemars_1::JsonSchemaAs<T> for Base85Bytes {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Base85Bytes".into()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        "base85rs::serde_with::Base85Bytes".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "array",
+            "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::serde_with::serde_as;
+    use serde::Serialize;
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Blob {
+        #[serde_as(as = "Base85")]
+        data: Vec<u8>,
+        #[serde_as(as = "Option<Base85>")]
+        extra: Option<Vec<u8>>,
+    }
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Key {
+        #[serde_as(as = "Base85")]
+        key: [u8; 4],
+    }
+
+    #[test]
+    fn serde_with_roundtrip() {
+        let blob = Blob {
+            data: b"aaaaa".to_vec(),
+            extra: None,
+        };
+        let json = serde_json::to_string(&blob).unwrap();
+        assert_eq!(json, r#"{"data":"VPRomVE","extra":null}"#);
+        assert_eq!(serde_json::from_str::<Blob>(&json).unwrap(), blob);
+    }
+
+    #[test]
+    fn serde_with_string_or_bytes() {
+        let blob = Blob {
+            data: b"aaaaa".to_vec(),
+            extra: Some(vec![1, 2]),
+        };
+        let json = r#"{"data":[97,97,97,97,97],"extra":"0Rj"}"#;
+        assert_eq!(serde_json::from_str::<Blob>(json).unwrap(), blob);
+
+        // CBOR carries the raw bytes
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&blob, &mut cbor).unwrap();
+        assert!(cbor.windows(5).any(|window| window == b"aaaaa"));
+        assert_eq!(
+            ciborium::from_reader::<Blob, _>(cbor.as_slice()).unwrap(),
+            blob
+        );
+
+        let err = serde_json::from_str::<Blob>(r#"{"data":5,"extra":null}"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected a base85 string or a byte sequence"));
+    }
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Forced {
+        #[serde_as(as = "Base85String")]
+        text: Vec<u8>,
+        #[serde_as(as = "Base85Bytes")]
+        raw: [u8; 2],
+    }
+
+    #[test]
+    fn serde_with_forced() {
+        let forced = Forced {
+            text: b"aaaaa".to_vec(),
+            raw: [1, 2],
+        };
+        let json = serde_json::to_string(&forced).unwrap();
+        assert_eq!(json, r#"{"text":"VPRomVE","raw":[1,2]}"#);
+        assert_eq!(serde_json::from_str::<Forced>(&json).unwrap(), forced);
+
+        // The string stays a string in CBOR
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&forced, &mut cbor).unwrap();
+        assert!(cbor.windows(7).any(|window| window == b"VPRomVE"));
+        assert_eq!(
+            ciborium::from_reader::<Forced, _>(cbor.as_slice()).unwrap(),
+            forced
+        );
+    }
+
+    #[test]
+    fn serde_with_invalid() {
+        let err = serde_json::from_str::<Blob>(r#"{"data":"VP]","extra":null}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid byte 0x5d at index 2"));
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn serde_with_schema() {
+        #[serde_as]
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        struct Described {
+            #[serde_as(as = "Base85")]
+            data: Vec<u8>,
+        }
+
+        let schema = schemars::schema_for!(Described);
+        let data = &schema.as_value()["properties"]["data"];
+        assert_eq!(data["type"], "string");
+        assert_eq!(data["pattern"], SCHEMA_PATTERN);
+
+        let pattern = regex::Regex::new(SCHEMA_PATTERN).unwrap();
+        let data: Vec<u8> = (0..=255).collect();
+        for len in 0..20 {
+            assert!(pattern.is_match(&crate::encode(&data[len * 12..len * 13])));
+        }
+        for invalid in ["V", "VP Ro", "VPRomV", "VP\"om"] {
+            assert!(!pattern.is_match(invalid), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn serde_with_array() {
+        let key = Key { key: *b"aaaa" };
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, r#"{"key":"VPRom"}"#);
+        assert_eq!(serde_json::from_str::<Key>(&json).unwrap(), key);
+
+        let err = serde_json::from_str::<Key>(r#"{"key":"VPRomVE"}"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("invalid length 5, expected 4 bytes"));
+    }
+}
@@ -0,0 +1,183 @@
+//! `wasm32` SIMD128 encoding and decoding of whole blocks of 4 groups
+//!
+//! Only compiled when targeting `wasm32` with the `simd128` target feature enabled, for instance
+//! with `RUSTFLAGS="-C target-feature=+simd128"`. The 4 groups of a block are held in the `u32x4`
+//! lanes of a vector: the encoder divides them by 85 with a multiplication by its reciprocal and
+//! looks the digits up with swizzles, the decoder multiplies them by 85. Trailing partial blocks,
+//! and blocks containing whitespaces or invalid characters, are left to the scalar code.
+
+use core::arch::wasm32::{
+    i32x4_mul, i32x4_shuffle, i32x4_sub, i8x16_shuffle, i8x16_swizzle, u16x8_narrow_i32x4, u32x4,
+    u32x4_add, u32x4_extract_lane, u32x4_splat, u64x2_extmul_high_u32x4, u64x2_extmul_low_u32x4,
+    u64x2_extract_lane, u64x2_shr, u8x16, u8x16_narrow_i16x8, u8x16_splat, u8x16_sub, v128,
+    v128_or,
+};
+
+use crate::{INVALID_DIGIT, RFC1924_ALPHABET, RFC1924_DIGITS};
+
+// `x / 85 == (x * DIV85_MAGIC) >> DIV85_SHIFT` for every `u32`
+const DIV85_MAGIC: u32 = 0xC0C0_C0C1;
+const DIV85_SHIFT: u32 = 38;
+
+// Big-endian value of the group of `block` starting at `start`
+fn group_value(block: &[u8], start: usize) -> u32 {
+    u32::from_be_bytes([
+        block[start],
+        block[start + 1],
+        block[start + 2],
+        block[start + 3],
+    ])
+}
+
+// Divide the `u32x4` lanes of `values` by 85, returning the quotients and the remainders
+fn div_rem_85(values: v128) -> (v128, v128) {
+    let magic = u32x4_splat(DIV85_MAGIC);
+    let low = u64x2_shr(u64x2_extmul_low_u32x4(values, magic), DIV85_SHIFT);
+    let high = u64x2_shr(u64x2_extmul_high_u32x4(values, magic), DIV85_SHIFT);
+    let quotients = i32x4_shuffle::<0, 2, 4, 6>(low, high);
+    let remainders = i32x4_sub(values, i32x4_mul(quotients, u32x4_splat(85)));
+    (quotients, remainders)
+}
+
+// The 16 characters of the alphabet starting at the digit `start`, 0 past its end
+fn alphabet_slice(start: usize) -> v128 {
+    let chr = |i: usize| RFC1924_ALPHABET.get(start + i).copied().unwrap_or(0);
+    u8x16(
+        chr(0),
+        chr(1),
+        chr(2),
+        chr(3),
+        chr(4),
+        chr(5),
+        chr(6),
+        chr(7),
+        chr(8),
+        chr(9),
+        chr(10),
+        chr(11),
+        chr(12),
+        chr(13),
+        chr(14),
+        chr(15),
+    )
+}
+
+// Replace every digit of the `u8x16` lanes of `digits` by its character, swizzling every slice of
+// the alphabet: indices out of a slice select 0.
+fn to_chars(digits: v128, alphabet: &[v128; 6]) -> v128 {
+    let mut chars = u8x16_splat(0);
+    let mut offset = digits;
+    for slice in alphabet {
+        chars = v128_or(chars, i8x16_swizzle(*slice, offset));
+        offset = u8x16_sub(offset, u8x16_splat(16));
+    }
+    chars
+}
+
+// Encode every whole block of 16 bytes of `data` into `out`, returning the number of bytes
+// consumed.
+pub(crate) fn encode_blocks(data: &[u8], out: &mut Vec<u8>) -> usize {
+    let alphabet = [0, 16, 32, 48, 64, 80].map(alphabet_slice);
+    let mut blocks = data.chunks_exact(16);
+    for block in &mut blocks {
+        let values = u32x4(
+            group_value(block, 0),
+            group_value(block, 4),
+            group_value(block, 8),
+            group_value(block, 12),
+        );
+        let (values, digit4) = div_rem_85(values);
+        let (values, digit3) = div_rem_85(values);
+        let (values, digit2) = div_rem_85(values);
+        let (digit0, digit1) = div_rem_85(values);
+
+        // Lanes: the digits 0, 1, 2 then 3 of the 4 groups, and the digits 4 of the groups
+        let first = u8x16_narrow_i16x8(
+            u16x8_narrow_i32x4(digit0, digit1),
+            u16x8_narrow_i32x4(digit2, digit3),
+        );
+        let last = u8x16_narrow_i16x8(
+            u16x8_narrow_i32x4(digit4, digit4),
+            u16x8_narrow_i32x4(digit4, digit4),
+        );
+        let head =
+            i8x16_shuffle::<0, 4, 8, 12, 16, 1, 5, 9, 13, 17, 2, 6, 10, 14, 18, 3>(first, last);
+        let tail = i8x16_shuffle::<7, 11, 15, 19, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0>(first, last);
+
+        let head = to_chars(head, &alphabet);
+        let tail = to_chars(tail, &alphabet);
+        out.extend(u64x2_extract_lane::<0>(head).to_le_bytes());
+        out.extend(u64x2_extract_lane::<1>(head).to_le_bytes());
+        out.extend(&u32x4_extract_lane::<0>(tail).to_le_bytes());
+    }
+    data.len() - blocks.remainder().len()
+}
+
+// Decode the leading blocks of 20 characters of `instr` into `out`, stopping at the first block
+// that isn't made of alphabet characters only, or that may overflow. Returns the number of characters consumed.
+pub(crate) fn decode_blocks(instr: &[u8], out: &mut Vec<u8>) -> usize {
+    let mut consumed = 0;
+    for block in instr.chunks_exact(20) {
+        let mut digits = [0; 20];
+        for (digit, &chr) in digits.iter_mut().zip(block) {
+            *digit = RFC1924_DIGITS[usize::from(chr)];
+        }
+        // Groups starting with digit 82 or above may exceed `u32::MAX`, the wrapping lanes can't
+        // tell: they are left to the scalar decoder, which checks them
+        if digits.contains(&INVALID_DIGIT) || digits.iter().step_by(5).any(|&digit| digit >= 82) {
+            break;
+        }
+
+        let base = u32x4_splat(85);
+        let mut values = u32x4_splat(0);
+        for pos in 0..5 {
+            let lanes = u32x4(
+                u32::from(digits[pos]),
+                u32::from(digits[5 + pos]),
+                u32::from(digits[10 + pos]),
+                u32::from(digits[15 + pos]),
+            );
+            values = u32x4_add(i32x4_mul(values, base), lanes);
+        }
+        let bytes = i8x16_swizzle(
+            values,
+            u8x16(3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12),
+        );
+        out.extend(u64x2_extract_lane::<0>(bytes).to_le_bytes());
+        out.extend(u64x2_extract_lane::<1>(bytes).to_le_bytes());
+        consumed += 20;
+    }
+    consumed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode_group;
+
+    #[test]
+    fn simd_encode_blocks() {
+        let data: Vec<u8> = (0..=255).chain([255; 20]).collect();
+        let mut out = Vec::new();
+        let consumed = encode_blocks(&data, &mut out);
+        assert_eq!(consumed, data.len() / 16 * 16);
+        let expected: Vec<u8> = data[..consumed]
+            .chunks(4)
+            .flat_map(|group| encode_group(group.try_into().unwrap()))
+            .collect();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn simd_decode_blocks() {
+        let mut out = Vec::new();
+        let consumed = decode_blocks(b"VPRomVPRomVPRomVPRomVPRomVPRomVPRomVPR m", &mut out);
+        assert_eq!(consumed, 20);
+        assert_eq!(out, b"aaaaaaaaaaaaaaaa");
+
+        // A group above `u32::MAX` is left to the scalar decoder
+        let mut out = Vec::new();
+        assert_eq!(decode_blocks(b"VPRomVPRom|NsC1VPRom", &mut out), 0);
+        assert_eq!(decode_blocks(b"|NsC0|NsC0|NsC0|NsC0", &mut out), 0);
+    }
+}
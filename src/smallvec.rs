@@ -0,0 +1,55 @@
+//! Decoding into a `SmallVec`, keeping short outputs on the stack
+
+use crate::{decode_with_sink, DecodeError};
+
+/// Number of decoded bytes kept inline by `decode_small()`
+pub const SMALL_CAPACITY: usize = 64;
+
+/// `decode_small()` try to decode a base85 encoded &str into a `SmallVec`
+///
+/// Up to `SMALL_CAPACITY` decoded bytes are stored inline, without any heap allocation. Longer
+/// outputs spill over to the heap.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet.
+///
+/// # Example
+///
+/// ```
+/// let decoded = base85rs::decode_small("VPRomVE").unwrap();
+/// assert_eq!(decoded.as_slice(), b"aaaaa");
+/// assert!(!decoded.spilled());
+/// ```
+pub fn decode_small(
+    instr: &str,
+) -> Result<::smallvec::SmallVec<[u8; SMALL_CAPACITY]>, DecodeError> {
+    let mut out = ::smallvec::SmallVec::new();
+    decode_with_sink(instr.as_bytes(), |bytes| {
+        out.extend_from_slice(bytes);
+        Ok(())
+    })?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn decode_small_inline() {
+        let data = [0xA5; SMALL_CAPACITY];
+        let decoded = decode_small(&encode(&data)).unwrap();
+        assert_eq!(decoded.as_slice(), data);
+        assert!(!decoded.spilled());
+    }
+
+    #[test]
+    fn decode_small_spilled() {
+        let data = [0xA5; SMALL_CAPACITY + 1];
+        let decoded = decode_small(&encode(&data)).unwrap();
+        assert_eq!(decoded.as_slice(), data);
+        assert!(decoded.spilled());
+    }
+}
@@ -0,0 +1,771 @@
+//! Streaming encoder and decoder, wrapping writers and readers
+
+use std::io::{self, BufRead, IoSlice, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::sync::Arc;
+
+use crate::pool::{Buffer, BufferPool};
+use crate::transcode::{base85_to_bytes, bytes_to_base85};
+use crate::{incremental, DecodeError, PositionedError};
+
+/// `EncoderWriter` encodes the bytes written to it into base85 text written to an inner writer
+///
+/// Complete groups of 4 bytes are encoded as soon as they are written, and the encoded characters
+/// are written to the inner writer once they fill the buffer, 8 KiB unless set by
+/// `with_buffer_size()`. The trailing 1 to 3 bytes can only be encoded once the data is known to
+/// be complete, which is what `finish()` does: `flush()` writes what is already encoded, but keeps
+/// the trailing bytes. Dropping an unfinished encoder finishes it, ignoring any error; call
+/// `finish()` to handle them.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Write;
+///
+/// let mut encoder = base85rs::EncoderWriter::new(Vec::new());
+/// encoder.write_all(b"aa").unwrap();
+/// encoder.write_all(b"aaa").unwrap();
+/// let encoded = encoder.finish().unwrap();
+/// assert_eq!(encoded, b"VPRomVE");
+/// ```
+#[derive(Debug)]
+pub struct EncoderWriter<W: Write> {
+    // `None` once finished
+    inner: Option<W>,
+    encoder: incremental::Encoder,
+    // Encoded characters not written yet to `inner`
+    pending: Buffer,
+    // Number of pending characters written at once
+    buffer_size: usize,
+}
+
+// Size of the buffers of `EncoderWriter` and `DecoderReader`, unless set by `with_buffer_size()`
+const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+fn finished() -> io::Error {
+    io::Error::other("encoder used after finish()")
+}
+
+// Write `pending` to `inner`, removing what has been written even when an error occurs.
+fn write_pending(inner: &mut impl Write, pending: &mut Vec<u8>) -> io::Result<()> {
+    while !pending.is_empty() {
+        match inner.write(pending) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => {
+                pending.drain(..written);
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+impl<W: Write> EncoderWriter<W> {
+    /// `new()` wraps `inner`, which receives the encoded text
+    pub fn new(inner: W) -> Self {
+        Self::with_buffer(inner, None)
+    }
+
+    /// `with_pool()` wraps `inner` as `new()` does, taking the buffer of the encoder from `pool`
+    /// and releasing it there once the encoder is dropped
+    pub fn with_pool(inner: W, pool: &Arc<BufferPool>) -> Self {
+        Self::with_buffer(inner, Some(pool))
+    }
+
+    fn with_buffer(inner: W, pool: Option<&Arc<BufferPool>>) -> Self {
+        Self {
+            inner: Some(inner),
+            encoder: incremental::Encoder::default(),
+            pending: Buffer::new(pool),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+        }
+    }
+
+    /// `with_buffer_size()` sets the number of encoded characters buffered before writing them to
+    /// the inner writer, at least 1
+    ///
+    /// Larger buffers mean fewer writes to the inner writer, smaller ones less memory.
+    #[must_use]
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size.max(1);
+        self
+    }
+
+    /// `finish()` encodes the trailing partial group, writes everything to the inner writer,
+    /// flushes it and gives it back
+    ///
+    /// If an error occurs, nothing is lost and `finish()` can be called again.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from the inner writer, and an error if the encoder was already
+    /// finished.
+    pub fn finish(&mut self) -> io::Result<W> {
+        let inner = self.inner.as_mut().ok_or_else(finished)?;
+        mem::take(&mut self.encoder).finish(|chars| self.pending.extend(chars));
+        write_pending(inner, &mut self.pending)?;
+        inner.flush()?;
+        trace_event!(debug, "encoder finished");
+        self.inner.take().ok_or_else(finished)
+    }
+}
+
+impl<W: Write> EncoderWriter<W> {
+    fn encode_slices(&mut self, slices: &[IoSlice<'_>]) -> io::Result<usize> {
+        let inner = self.inner.as_mut().ok_or_else(finished)?;
+        // Errors are reported before accepting any new data
+        if self.pending.len() >= self.buffer_size {
+            write_pending(inner, &mut self.pending)?;
+        }
+        let mut len = 0;
+        for slice in slices {
+            self.encoder
+                .update(slice, |chars| self.pending.extend(chars));
+            len += slice.len();
+        }
+        trace_event!(
+            trace,
+            bytes = len,
+            pending = self.pending.len(),
+            "data encoded"
+        );
+        // The data is accepted at this point, a failure is reported by the next call
+        if self.pending.len() >= self.buffer_size {
+            let _ = write_pending(inner, &mut self.pending);
+        }
+        Ok(len)
+    }
+}
+
+impl<W: Write> Write for EncoderWriter<W> {
+    /// Encodes `data`, writing the buffered characters to the inner writer once they fill the
+    /// buffer
+    ///
+    /// Characters not accepted by the inner writer are kept, and written before the next data.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.encode_slices(&[IoSlice::new(data)])
+    }
+
+    /// Encodes all of `slices` as if they were concatenated, groups spanning over them
+    fn write_vectored(&mut self, slices: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.encode_slices(slices)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().ok_or_else(finished)?;
+        write_pending(inner, &mut self.pending)?;
+        inner.flush()
+    }
+}
+
+impl<W: Write> Drop for EncoderWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.finish();
+        }
+    }
+}
+
+/// `DecoderReader` decodes the base85 text read from an inner reader
+///
+/// Spaces and line breaks are skipped, and an invalid character is reported as an
+/// `io::ErrorKind::InvalidData` error wrapping a `PositionedError`: the `DecodeError` along with
+/// its offset, line and column in the encoded stream, to locate corruption in large files.
+/// Decoded bytes are buffered, and exposed through `BufRead` without any further copy.
+///
+/// When the inner reader implements `Seek`, so does the decoder: a decoded offset is translated to
+/// the offset of its group in the inner reader, counted from its start. This relies on every group
+/// being 5 consecutive characters, seeking is only meaningful for inputs without whitespaces.
+///
+/// # Example
+///
+/// ```
+/// use std::io::{Cursor, Read, Seek, SeekFrom};
+///
+/// let mut decoder = base85rs::DecoderReader::new(Cursor::new("VPRomVPRomVE"));
+/// decoder.seek(SeekFrom::Start(6)).unwrap();
+/// let mut decoded = Vec::new();
+/// decoder.read_to_end(&mut decoded).unwrap();
+/// assert_eq!(decoded, b"aaa");
+/// ```
+#[derive(Debug)]
+pub struct DecoderReader<R: Read> {
+    inner: R,
+    decoder: incremental::Decoder,
+    // Characters read from `inner`
+    buffer: Buffer,
+    // Decoded bytes, read up to `consumed`
+    pending: Buffer,
+    consumed: usize,
+    // Decoded bytes to drop before the next read, to land inside a group after seeking
+    discard: usize,
+    // Offset in the decoded data of the next byte read
+    position: u64,
+    // Line of the next character decoded, and offset of the start of this line
+    line: u64,
+    line_start: u64,
+    // Line and start of line of the first digit of the current group
+    group_line: (u64, u64),
+    finished: bool,
+}
+
+impl<R: Read> DecoderReader<R> {
+    /// `new()` wraps `inner`, which provides the base85 text
+    pub fn new(inner: R) -> Self {
+        Self::with_buffers(inner, None)
+    }
+
+    /// `with_pool()` wraps `inner` as `new()` does, taking the buffers of the decoder from `pool`
+    /// and releasing them there once the decoder is dropped
+    pub fn with_pool(inner: R, pool: &Arc<BufferPool>) -> Self {
+        Self::with_buffers(inner, Some(pool))
+    }
+
+    fn with_buffers(inner: R, pool: Option<&Arc<BufferPool>>) -> Self {
+        let mut buffer = Buffer::new(pool);
+        buffer.resize(DEFAULT_BUFFER_SIZE, 0);
+        Self {
+            inner,
+            decoder: incremental::Decoder::default(),
+            buffer,
+            pending: Buffer::new(pool),
+            consumed: 0,
+            discard: 0,
+            position: 0,
+            line: 1,
+            line_start: 0,
+            group_line: (1, 0),
+            finished: false,
+        }
+    }
+
+    /// `with_buffer_size()` sets the number of characters read at once from the inner reader, at
+    /// least 1 and 8 KiB by default
+    ///
+    /// Larger buffers mean fewer reads from the inner reader, smaller ones less memory.
+    #[must_use]
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer.resize(size.max(1), 0);
+        self.buffer.shrink_to_fit();
+        self
+    }
+
+    /// `into_inner()` gives the inner reader back, dropping the decoded bytes not read yet
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    // Decode more of the inner reader into `pending`, or the trailing partial group at its end.
+    fn fill(&mut self) -> io::Result<()> {
+        if self.consumed == self.pending.len() {
+            self.pending.clear();
+            self.consumed = 0;
+        }
+        let len = loop {
+            match self.inner.read(&mut self.buffer) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                result => break result?,
+            }
+        };
+        let start = self.decoder.index();
+        let decoded = if len == 0 {
+            self.finished = true;
+            mem::take(&mut self.decoder).finish(|bytes| {
+                self.pending.extend(bytes);
+                Ok(())
+            })
+        } else {
+            self.decode_lines(len)
+        };
+        if let Err(error) = decoded {
+            // Overflowing groups are located at their first digit, possibly on a previous line.
+            // Errors without an index are located at the last character decoded, the last one of
+            // the input if the decoder was finishing.
+            let (offset, (line, line_start)) = match error {
+                DecodeError::InvalidByte { index, .. } => (index, (self.line, self.line_start)),
+                DecodeError::GroupOverflow { index } => (index, self.group_line),
+                _ if self.finished => (start.saturating_sub(1), (self.line, self.line_start)),
+                _ => (
+                    self.decoder.index().saturating_sub(1),
+                    (self.line, self.line_start),
+                ),
+            };
+            let offset = offset as u64;
+            let err = PositionedError {
+                error,
+                offset,
+                line,
+                column: offset.saturating_sub(line_start) + 1,
+            };
+            trace_event!(debug, error = %err, "invalid input");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+        trace_event!(
+            trace,
+            chars = len,
+            pending = self.pending.len() - self.consumed,
+            "input decoded"
+        );
+        if self.finished {
+            trace_event!(
+                debug,
+                decoded = self.position + (self.pending.len() - self.consumed) as u64,
+                "decoder reached the end of its input"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> DecoderReader<R> {
+    // Decode the `len` characters read into `buffer`, line by line, skipping the line breaks and
+    // keeping track of the current line.
+    fn decode_lines(&mut self, len: usize) -> Result<(), DecodeError> {
+        let start = self.decoder.index();
+        let mut sink = |bytes: &[u8]| {
+            self.pending.extend(bytes);
+            Ok(())
+        };
+        let mut offset = 0;
+        for segment in self.buffer[..len].split(|&chr| matches!(chr, b'\n' | b'\r')) {
+            self.decoder.set_index(start + offset);
+            let decoded = self.decoder.update(segment, &mut sink);
+            if self.decoder.group_start() >= start + offset {
+                self.group_line = (self.line, self.line_start);
+            }
+            decoded?;
+            offset += segment.len();
+            if self.buffer.get(offset) == Some(&b'\n') && offset < len {
+                self.line += 1;
+                self.line_start = (start + offset + 1) as u64;
+            }
+            offset += 1;
+        }
+        self.decoder.set_index(start + len);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecoderReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = buf.len().min(available.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: Read> BufRead for DecoderReader<R> {
+    /// Returns the decoded bytes not read yet, decoding more of the inner reader when there are
+    /// none
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.pending.len() - self.consumed <= self.discard && !self.finished {
+            self.fill()?;
+        }
+        self.consumed += self.discard.min(self.pending.len() - self.consumed);
+        self.discard = 0;
+        Ok(&self.pending[self.consumed..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = amt.min(self.pending.len() - self.consumed);
+        self.consumed += amt;
+        self.position += amt as u64;
+    }
+}
+
+impl<R: Read + Seek> Seek for DecoderReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => {
+                let len = self.inner.seek(SeekFrom::End(0))?;
+                (len / 5 * 4 + (len % 5).saturating_sub(1)).checked_add_signed(delta)
+            }
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        let start = target / 4 * 5;
+        self.inner.seek(SeekFrom::Start(start))?;
+        self.decoder = incremental::Decoder::default();
+        self.decoder
+            .set_index(usize::try_from(start).unwrap_or(usize::MAX));
+        self.pending.clear();
+        self.consumed = 0;
+        self.discard = usize::try_from(target % 4).unwrap_or(0);
+        self.position = target;
+        // Lines are unknown past a seek, the input is assumed to have none
+        (self.line, self.line_start) = (1, 0);
+        self.group_line = (1, 0);
+        self.finished = false;
+        Ok(target)
+    }
+}
+
+/// `encode_reader()` encodes everything read from `reader` into a base85 `String`
+///
+/// To write the encoded text to a writer instead, use `transcode::bytes_to_base85()`.
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader`.
+///
+/// # Example
+///
+/// ```
+/// let encoded = base85rs::encode_reader("aaaaa".as_bytes()).unwrap();
+/// assert_eq!(encoded, "VPRomVE");
+/// ```
+pub fn encode_reader(mut reader: impl Read) -> io::Result<String> {
+    let mut encoder = EncoderWriter::new(Vec::new());
+    io::copy(&mut reader, &mut encoder)?;
+    let outdata = encoder.finish()?;
+    Ok(String::from_utf8(outdata).unwrap_or_default())
+}
+
+/// `copy_encode()` encodes everything read from `reader` into `writer`, like `io::copy()`
+///
+/// Data is streamed through an internal buffer. The number of bytes read from `reader` is
+/// returned.
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`.
+///
+/// # Example
+///
+/// ```
+/// let (mut reader, mut writer) = ("aaaaa".as_bytes(), Vec::new());
+/// assert_eq!(base85rs::copy_encode(&mut reader, &mut writer).unwrap(), 5);
+/// assert_eq!(writer, b"VPRomVE");
+/// ```
+pub fn copy_encode<R: Read + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+) -> io::Result<u64> {
+    bytes_to_base85(reader, writer)
+}
+
+/// `copy_decode()` decodes everything read from `reader` into `writer`, like `io::copy()`
+///
+/// Data is streamed through an internal buffer, whitespaces are skipped. The number of bytes
+/// written to `writer` is returned.
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`, and an `io::ErrorKind::InvalidData` error
+/// wrapping a `DecodeError` if the input isn't valid base85.
+///
+/// # Example
+///
+/// ```
+/// let (mut reader, mut writer) = ("VPRom VE".as_bytes(), Vec::new());
+/// assert_eq!(base85rs::copy_decode(&mut reader, &mut writer).unwrap(), 5);
+/// assert_eq!(writer, b"aaaaa");
+/// ```
+pub fn copy_decode<R: Read + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+) -> io::Result<u64> {
+    base85_to_bytes(reader, writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn encoder_writer_split_input() {
+        let data = "countercompetition".as_bytes();
+        for split in 0..data.len() {
+            let mut encoder = EncoderWriter::new(Vec::new());
+            encoder.write_all(&data[..split]).unwrap();
+            encoder.flush().unwrap();
+            encoder.write_all(&data[split..]).unwrap();
+            assert_eq!(encoder.finish().unwrap(), encode(data).as_bytes());
+        }
+    }
+
+    #[test]
+    fn encoder_writer_vectored() {
+        let data = "countercompetition".as_bytes();
+        let mut encoder = EncoderWriter::new(Vec::new());
+        let slices = [
+            IoSlice::new(&data[..1]),
+            IoSlice::new(&data[1..6]),
+            IoSlice::new(&data[6..]),
+        ];
+        assert_eq!(encoder.write_vectored(&slices).unwrap(), data.len());
+        assert_eq!(encoder.finish().unwrap(), encode(data).as_bytes());
+    }
+
+    #[test]
+    fn encoder_writer_drop_finishes() {
+        let mut outdata = Vec::new();
+        {
+            let mut encoder = EncoderWriter::new(&mut outdata);
+            encoder.write_all(b"aaaaa").unwrap();
+        }
+        assert_eq!(outdata, b"VPRomVE");
+    }
+
+    #[test]
+    fn encoder_writer_after_finish() {
+        let mut encoder = EncoderWriter::new(Vec::new());
+        encoder.write_all(b"a").unwrap();
+        assert_eq!(encoder.finish().unwrap(), b"VE");
+        assert!(encoder.write(b"a").is_err());
+        assert!(encoder.flush().is_err());
+        assert!(encoder.finish().is_err());
+    }
+
+    #[test]
+    fn decoder_reader_read() {
+        let data: Vec<u8> = (0..10_000_u32).map(|i| (i % 251) as u8).collect();
+        let spaced: String = encode(&data).chars().flat_map(|chr| [chr, ' ']).collect();
+        let mut decoded = Vec::new();
+        DecoderReader::new(spaced.as_bytes())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
+
+        let err = DecoderReader::new("VPRom]".as_bytes())
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn decoder_reader_error_position() {
+        // Line breaks are skipped, and counted
+        let mut input = "VPRom VPRom\r\n".repeat(2);
+        assert_eq!(decode_reader(&input, 3).unwrap(), b"aaaaaaaaaaaaaaaa");
+        input.push_str("VPRom\nVP]om");
+        for buffer_size in [1, 4, 7, 100] {
+            let err = decode_reader(&input, buffer_size).unwrap_err();
+            assert_eq!(
+                err.error,
+                DecodeError::InvalidByte {
+                    index: 34,
+                    byte: b']'
+                }
+            );
+            assert_eq!((err.offset, err.line, err.column), (34, 4, 3));
+        }
+
+        // Overflowing groups are located at their first character
+        let err = decode_reader("VPRom\n  |NsC1VE", 4).unwrap_err();
+        assert_eq!(err.error, DecodeError::GroupOverflow { index: 8 });
+        assert_eq!((err.offset, err.line, err.column), (8, 2, 3));
+        let err = decode_reader("VPRom|Nt", 4).unwrap_err();
+        assert_eq!((err.offset, err.column), (5, 6));
+        assert_eq!(
+            err.to_string(),
+            "group at index 5 exceeds 32 bits at line 1, column 6 (offset 5)"
+        );
+        for buffer_size in [1, 3, 100] {
+            let err = decode_reader("VPRom\n |N\r\n\nsC1", buffer_size).unwrap_err();
+            assert_eq!((err.offset, err.line, err.column), (7, 2, 2));
+        }
+    }
+
+    fn decode_reader(input: &str, buffer_size: usize) -> Result<Vec<u8>, PositionedError> {
+        let mut decoded = Vec::new();
+        DecoderReader::new(input.as_bytes())
+            .with_buffer_size(buffer_size)
+            .read_to_end(&mut decoded)
+            .map_err(|err| *err.into_inner().unwrap().downcast().unwrap())?;
+        Ok(decoded)
+    }
+
+    // Writer and reader counting the calls made to them
+    #[derive(Default)]
+    struct Counting {
+        data: Vec<u8>,
+        calls: usize,
+    }
+
+    impl Write for Counting {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.calls += 1;
+            self.data.extend(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for Counting {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.calls += 1;
+            let len = buf.len().min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data.drain(..len);
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn streams_buffer_size() {
+        let data = vec![0xA5; 40_000];
+        // 125 characters per write, written by 5 writes with a 512 bytes buffer
+        for (size, calls) in [(512, 80), (1 << 20, 1)] {
+            let mut encoder = EncoderWriter::new(Counting::default()).with_buffer_size(size);
+            for chunk in data.chunks(100) {
+                encoder.write_all(chunk).unwrap();
+            }
+            let inner = encoder.finish().unwrap();
+            assert_eq!(inner.data, encode(&data).as_bytes());
+            assert_eq!(inner.calls, calls);
+
+            let reader = Counting {
+                data: inner.data,
+                calls: 0,
+            };
+            let mut decoder = DecoderReader::new(reader).with_buffer_size(size);
+            let mut output = Vec::new();
+            decoder.read_to_end(&mut output).unwrap();
+            assert_eq!(output, data);
+            // Including the read reaching the end
+            assert_eq!(decoder.into_inner().calls, 50_000_usize.div_ceil(size) + 1);
+        }
+    }
+
+    #[test]
+    fn streams_with_pool() {
+        let pool = Arc::new(BufferPool::new(8));
+        let data: Vec<u8> = (0..10_000_u32).map(|i| (i % 251) as u8).collect();
+        for _ in 0..3 {
+            let mut encoder = EncoderWriter::with_pool(Vec::new(), &pool);
+            encoder.write_all(&data).unwrap();
+            let text = encoder.finish().unwrap();
+            drop(encoder);
+
+            let mut decoded = Vec::new();
+            DecoderReader::with_pool(text.as_slice(), &pool)
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, data);
+            // The encoder buffer and the two decoder buffers
+            assert_eq!(pool.idle(), 2);
+        }
+    }
+
+    #[test]
+    fn decoder_reader_buf_read() {
+        let encoded = encode(b"first line\nsecond line\nlast");
+        let lines: Vec<String> = DecoderReader::new(encoded.as_bytes())
+            .lines()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(lines, ["first line", "second line", "last"]);
+
+        let mut decoder = DecoderReader::new(io::Cursor::new(encoded));
+        decoder.seek(SeekFrom::Start(6)).unwrap();
+        let mut word = Vec::new();
+        decoder.read_until(b'\n', &mut word).unwrap();
+        assert_eq!(word, b"line\n");
+        assert_eq!(decoder.stream_position().unwrap(), 11);
+    }
+
+    #[test]
+    fn decoder_reader_seek() {
+        let data: Vec<u8> = (0..10_003_u32).map(|i| (i % 251) as u8).collect();
+        let mut decoder = DecoderReader::new(io::Cursor::new(encode(&data)));
+        let mut buffer = [0; 7];
+        for (pos, offset) in [
+            (SeekFrom::Start(4_097), 4_097),
+            (SeekFrom::Current(-3_000), 1_104),
+            (SeekFrom::End(-7), 9_996),
+        ] {
+            assert_eq!(decoder.seek(pos).unwrap(), offset as u64);
+            decoder.read_exact(&mut buffer).unwrap();
+            assert_eq!(buffer, data[offset..offset + 7]);
+            assert_eq!(decoder.stream_position().unwrap(), offset as u64 + 7);
+        }
+        assert_eq!(decoder.read(&mut buffer).unwrap(), 0);
+        assert!(decoder.seek(SeekFrom::Current(-20_000)).is_err());
+        assert_eq!(decoder.seek(SeekFrom::Start(20_000)).unwrap(), 20_000);
+        assert_eq!(decoder.read(&mut buffer).unwrap(), 0);
+    }
+
+    #[test]
+    fn encode_reader_chunks() {
+        let data: Vec<u8> = (0..100_000_u32).map(|i| (i % 251) as u8).collect();
+        // Chained readers hand the data over in several reads
+        let reader = data[..3].chain(&data[3..50_001]).chain(&data[50_001..]);
+        assert_eq!(encode_reader(reader).unwrap(), encode(&data));
+        assert_eq!(encode_reader(io::empty()).unwrap(), "");
+    }
+
+    #[test]
+    fn copy_roundtrip() {
+        let data: Vec<u8> = (0..100_000_u32).map(|i| (i % 251) as u8).collect();
+        let (mut encoded, mut decoded) = (Vec::new(), Vec::new());
+        assert_eq!(
+            copy_encode(&mut data.as_slice(), &mut encoded).unwrap(),
+            100_000
+        );
+        assert_eq!(
+            copy_decode(&mut encoded.as_slice(), &mut decoded).unwrap(),
+            100_000
+        );
+        assert_eq!(decoded, data);
+
+        let err = copy_decode(&mut "VP]".as_bytes(), &mut io::sink()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    // Writer accepting a single byte per call, and failing every other call
+    struct Flaky {
+        outdata: Vec<u8>,
+        fail: bool,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.fail = !self.fail;
+            if self.fail {
+                return Err(io::Error::other("flaky"));
+            }
+            self.outdata.push(data[0]);
+            Ok(1)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn encoder_writer_retries() {
+        let mut encoder = EncoderWriter::new(Flaky {
+            outdata: Vec::new(),
+            fail: false,
+        });
+        let data = b"countercompetition";
+        let mut written = 0;
+        while written < data.len() {
+            if let Ok(len) = encoder.write(&data[written..]) {
+                written += len;
+            }
+        }
+        let inner = loop {
+            if let Ok(inner) = encoder.finish() {
+                break inner;
+            }
+        };
+        assert_eq!(inner.outdata, encode(data).as_bytes());
+    }
+}
@@ -0,0 +1,668 @@
+//! Streaming conversion between base85 and raw bytes, base64 or hex text
+//!
+//! Data goes through a small internal buffer, the whole decoded payload is never materialized.
+//! Every function returns the number of decoded bytes that went through the conversion. `transcode()`
+//! is the single entry point taking a direction and a `Codec`.
+//!
+//! # Example
+//!
+//! ```
+//! let mut hex = Vec::new();
+//! let len = base85rs::transcode::base85_to_hex("VPRomVE".as_bytes(), &mut hex).unwrap();
+//! assert_eq!(len, 5);
+//! assert_eq!(hex, b"6161616161");
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::codec::{StreamDecoder, StreamEncoder};
+use crate::{incremental, Codec, DecodeError};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+static BASE64_CHARS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+static HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+// Text to bytes half of a conversion
+trait Decode {
+    fn update(&mut self, instr: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError>;
+    fn finish(self, out: &mut Vec<u8>) -> Result<(), DecodeError>;
+}
+
+// Bytes to text half of a conversion
+trait Encode {
+    fn update(&mut self, data: &[u8], out: &mut Vec<u8>);
+    fn finish(self, out: &mut Vec<u8>);
+}
+
+impl Decode for incremental::Decoder {
+    fn update(&mut self, instr: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.update(instr, |bytes| {
+            out.extend(bytes);
+            Ok(())
+        })
+    }
+
+    fn finish(self, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.finish(|bytes| {
+            out.extend(bytes);
+            Ok(())
+        })
+    }
+}
+
+impl Encode for incremental::Encoder {
+    fn update(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        self.update(data, |chars| out.extend(chars));
+    }
+
+    fn finish(self, out: &mut Vec<u8>) {
+        self.finish(|chars| out.extend(chars));
+    }
+}
+
+impl Decode for StreamDecoder<'_> {
+    fn update(&mut self, instr: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.update(instr, out)
+    }
+
+    fn finish(self, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.finish(out)
+    }
+}
+
+impl Encode for StreamEncoder<'_> {
+    fn update(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        self.update(data, out);
+    }
+
+    fn finish(self, out: &mut Vec<u8>) {
+        self.finish(out);
+    }
+}
+
+// Raw bytes, on either side of a conversion
+struct Raw;
+
+impl Decode for Raw {
+    fn update(&mut self, instr: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        out.extend(instr);
+        Ok(())
+    }
+
+    fn finish(self, _out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        Ok(())
+    }
+}
+
+impl Encode for Raw {
+    fn update(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        out.extend(data);
+    }
+
+    fn finish(self, _out: &mut Vec<u8>) {}
+}
+
+#[derive(Default)]
+struct HexEncoder;
+
+impl Encode for HexEncoder {
+    fn update(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        for byte in data {
+            out.extend([
+                HEX_CHARS[usize::from(byte >> 4)],
+                HEX_CHARS[usize::from(byte & 0xf)],
+            ]);
+        }
+    }
+
+    fn finish(self, _out: &mut Vec<u8>) {}
+}
+
+#[derive(Default)]
+struct HexDecoder {
+    high: Option<u8>,
+    index: usize,
+}
+
+impl Decode for HexDecoder {
+    fn update(&mut self, instr: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        for &chr in instr {
+            let index = self.index;
+            self.index += 1;
+            if chr.is_ascii_whitespace() {
+                continue;
+            }
+            let value = char::from(chr)
+                .to_digit(16)
+                .and_then(|value| u8::try_from(value).ok())
+                .ok_or(DecodeError::InvalidByte { index, byte: chr })?;
+            match self.high.take() {
+                Some(high) => out.push(high << 4 | value),
+                None => self.high = Some(value),
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, _out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        match self.high {
+            Some(_) => Err(DecodeError::InvalidLength),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Base64Encoder {
+    tail: [u8; 3],
+    filled: usize,
+}
+
+impl Base64Encoder {
+    fn encode_chunk(chunk: [u8; 3], len: usize, out: &mut Vec<u8>) {
+        let value = u32::from_be_bytes([0, chunk[0], chunk[1], chunk[2]]);
+        for shift in [18, 12, 6, 0].into_iter().take(len + 1) {
+            out.push(
+                BASE64_CHARS[usize::from(u8::try_from(value >> shift & 0x3f).unwrap_or_default())],
+            );
+        }
+        out.extend(std::iter::repeat(b'=').take(3 - len));
+    }
+}
+
+impl Encode for Base64Encoder {
+    fn update(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        for &byte in data {
+            self.tail[self.filled] = byte;
+            self.filled += 1;
+            if self.filled == 3 {
+                Self::encode_chunk(self.tail, 3, out);
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn finish(mut self, out: &mut Vec<u8>) {
+        if self.filled != 0 {
+            self.tail[self.filled..].fill(0);
+            Self::encode_chunk(self.tail, self.filled, out);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Base64Decoder {
+    digits: [u8; 4],
+    filled: usize,
+    padding: usize,
+    index: usize,
+}
+
+impl Base64Decoder {
+    fn decode_chunk(&self, out: &mut Vec<u8>) {
+        let value = self
+            .digits
+            .iter()
+            .fold(0, |acc: u32, &digit| acc << 6 | u32::from(digit));
+        out.extend(&value.to_be_bytes()[1..self.filled]);
+    }
+}
+
+impl Decode for Base64Decoder {
+    fn update(&mut self, instr: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        for &chr in instr {
+            let index = self.index;
+            self.index += 1;
+            if chr.is_ascii_whitespace() {
+                continue;
+            }
+            let invalid = DecodeError::InvalidByte { index, byte: chr };
+            if chr == b'=' {
+                // Padding completes a group of at least 2 digits, and nothing follows it
+                if self.filled + self.padding < 2 || self.filled + self.padding == 4 {
+                    return Err(invalid);
+                }
+                self.padding += 1;
+                continue;
+            }
+            if self.padding != 0 {
+                return Err(invalid);
+            }
+            let position = BASE64_CHARS.iter().position(|&c| c == chr).ok_or(invalid)?;
+            self.digits[self.filled] = u8::try_from(position).unwrap_or_default();
+            self.filled += 1;
+            if self.filled == 4 {
+                self.decode_chunk(out);
+                self.filled = 0;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        match self.filled {
+            0 => Ok(()),
+            1 => Err(DecodeError::InvalidLength),
+            _ => {
+                self.digits[self.filled..].fill(0);
+                self.decode_chunk(out);
+                Ok(())
+            }
+        }
+    }
+}
+
+// Read, convert and write chunks until the end of `reader`.
+fn pipe(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    mut decoder: impl Decode,
+    mut encoder: impl Encode,
+) -> io::Result<u64> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("transcode").entered();
+    let invalid_data = |err: DecodeError| {
+        trace_event!(debug, error = %err, "invalid input");
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    };
+
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut bytes = Vec::new();
+    let mut text = Vec::new();
+    let mut total = 0;
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        decoder
+            .update(&buffer[..read], &mut bytes)
+            .map_err(invalid_data)?;
+        encoder.update(&bytes, &mut text);
+        trace_event!(
+            trace,
+            read,
+            bytes = bytes.len(),
+            written = text.len(),
+            "chunk converted"
+        );
+        writer.write_all(&text)?;
+        total += bytes.len() as u64;
+        bytes.clear();
+        text.clear();
+    }
+
+    decoder.finish(&mut bytes).map_err(invalid_data)?;
+    encoder.update(&bytes, &mut text);
+    encoder.finish(&mut text);
+    writer.write_all(&text)?;
+    writer.flush()?;
+    total += bytes.len() as u64;
+    trace_event!(debug, bytes = total, "conversion finished");
+    Ok(total)
+}
+
+/// `bytes_to_base85()` encodes the bytes from `reader` into base85 text written to `writer`
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`.
+pub fn bytes_to_base85(reader: impl Read, writer: impl Write) -> io::Result<u64> {
+    pipe(reader, writer, Raw, incremental::Encoder::default())
+}
+
+/// `base85_to_bytes()` decodes base85 text from `reader` into bytes written to `writer`
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`, and an `io::ErrorKind::InvalidData` error
+/// wrapping a `DecodeError` if the input isn't valid base85.
+pub fn base85_to_bytes(reader: impl Read, writer: impl Write) -> io::Result<u64> {
+    pipe(reader, writer, incremental::Decoder::default(), Raw)
+}
+
+/// `base85_to_hex()` converts base85 text from `reader` into lowercase hex text written to `writer`
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`, and an `io::ErrorKind::InvalidData` error
+/// wrapping a `DecodeError` if the input isn't valid base85.
+pub fn base85_to_hex(reader: impl Read, writer: impl Write) -> io::Result<u64> {
+    pipe(reader, writer, incremental::Decoder::default(), HexEncoder)
+}
+
+/// `base85_to_base64()` converts base85 text from `reader` into padded base64 text written to `writer`
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`, and an `io::ErrorKind::InvalidData` error
+/// wrapping a `DecodeError` if the input isn't valid base85.
+pub fn base85_to_base64(reader: impl Read, writer: impl Write) -> io::Result<u64> {
+    pipe(
+        reader,
+        writer,
+        incremental::Decoder::default(),
+        Base64Encoder::default(),
+    )
+}
+
+/// `hex_to_base85()` converts hex text from `reader` into base85 text written to `writer`
+///
+/// Whitespaces are ignored and both cases are accepted.
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`, and an `io::ErrorKind::InvalidData` error
+/// wrapping a `DecodeError` if the input isn't valid hex.
+pub fn hex_to_base85(reader: impl Read, writer: impl Write) -> io::Result<u64> {
+    pipe(
+        reader,
+        writer,
+        HexDecoder::default(),
+        incremental::Encoder::default(),
+    )
+}
+
+/// `base64_to_base85()` converts standard base64 text from `reader` into base85 text written to `writer`
+///
+/// Whitespaces are ignored and padding is optional.
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`, and an `io::ErrorKind::InvalidData` error
+/// wrapping a `DecodeError` if the input isn't valid base64.
+pub fn base64_to_base85(reader: impl Read, writer: impl Write) -> io::Result<u64> {
+    pipe(
+        reader,
+        writer,
+        Base64Decoder::default(),
+        incremental::Encoder::default(),
+    )
+}
+
+/// Conversion done by `transcode()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Direction {
+    /// Raw bytes to base85 text
+    Encode,
+    /// Base85 text to raw bytes
+    Decode,
+}
+
+/// `transcode()` encodes or decodes everything read from `reader` into `writer`, with `codec`
+///
+/// Data is streamed through a small buffer, as by `bytes_to_base85()` and `base85_to_bytes()`.
+/// Only three options need the whole input, which is then read before converting it at once: a
+/// checksum, covering all the data, `Padding::Counted`, whose count comes before the groups, and
+/// `Variant::Ascii85`.
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader` or `writer`, and an `io::ErrorKind::InvalidData` error
+/// wrapping a `DecodeError` if the input can't be decoded by `codec`. When streaming, this is the
+/// first error in the order of the input: a missing suffix or an overlong line comes after an
+/// invalid byte before it.
+///
+/// # Example
+///
+/// ```
+/// use base85rs::transcode::{transcode, Direction};
+///
+/// let codec = base85rs::Base85Builder::new().wrap(5).build();
+/// let mut encoded = Vec::new();
+/// let len = transcode("aaaaa".as_bytes(), &mut encoded, Direction::Encode, &codec).unwrap();
+/// assert_eq!((len, encoded.as_slice()), (5, b"VPRom\nVE".as_slice()));
+/// ```
+pub fn transcode(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    direction: Direction,
+    codec: &Codec,
+) -> io::Result<u64> {
+    if *codec == Codec::default() {
+        return match direction {
+            Direction::Encode => bytes_to_base85(reader, writer),
+            Direction::Decode => base85_to_bytes(reader, writer),
+        };
+    }
+    match direction {
+        Direction::Encode => {
+            if let Some(encoder) = codec.stream_encoder() {
+                return pipe(reader, writer, Raw, encoder);
+            }
+        }
+        Direction::Decode => {
+            if let Some(decoder) = codec.stream_decoder() {
+                return pipe(reader, writer, decoder, Raw);
+            }
+        }
+    }
+
+    let invalid_data = |err| io::Error::new(io::ErrorKind::InvalidData, err);
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    let (outdata, len) = match direction {
+        Direction::Encode => {
+            let len = input.len();
+            (codec.encode(&input).into_bytes(), len)
+        }
+        Direction::Decode => {
+            let text = String::from_utf8(input).map_err(|err| {
+                let index = err.utf8_error().valid_up_to();
+                invalid_data(DecodeError::InvalidByte {
+                    index,
+                    byte: err.as_bytes()[index],
+                })
+            })?;
+            let decoded = codec.decode(&text).map_err(invalid_data)?;
+            let len = decoded.len();
+            (decoded, len)
+        }
+    };
+    writer.write_all(&outdata)?;
+    writer.flush()?;
+    Ok(len as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Conversion = fn(&mut dyn Read, &mut Vec<u8>) -> io::Result<u64>;
+
+    // Run a conversion, reading the input one byte at a time to exercise the internal state
+    fn convert(function: Conversion, input: &str) -> io::Result<String> {
+        let mut outdata = Vec::new();
+        let mut reader = io::BufReader::with_capacity(1, input.as_bytes());
+        function(&mut reader, &mut outdata)?;
+        Ok(String::from_utf8(outdata).unwrap())
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        let encoded = convert(|r, w| bytes_to_base85(r, w), "pollenless").unwrap();
+        assert_eq!(encoded, "aBpmEWo~R`b8`");
+        let decoded = convert(|r, w| base85_to_bytes(r, w), &encoded).unwrap();
+        assert_eq!(decoded, "pollenless");
+    }
+
+    #[test]
+    fn base85_to_hex_word() {
+        let hex = convert(|r, w| base85_to_hex(r, w), "aBpmEWo~R`b8`").unwrap();
+        assert_eq!(hex, "706f6c6c656e6c657373");
+    }
+
+    #[test]
+    fn hex_to_base85_word() {
+        let encoded = convert(|r, w| hex_to_base85(r, w), "706F6C6C656E 6C657373\n").unwrap();
+        assert_eq!(encoded, "aBpmEWo~R`b8`");
+    }
+
+    #[test]
+    fn base85_to_base64_word_set() {
+        let wordlist = [
+            ("a%F63ZE192bZKvH", "cmVsaW1pdGF0aW9u"),
+            ("aBpmEWo~R`b8`", "cG9sbGVubGVzcw=="),
+            ("V_|k>Yh`6{WpV", "Y2F2ZWtlZXBlcg=="),
+            ("ZE0h2Z*y;LX<=*", "bWljcm9zb21pYWw="),
+        ];
+        for (encoded, base64) in wordlist {
+            assert_eq!(
+                convert(|r, w| base85_to_base64(r, w), encoded).unwrap(),
+                base64
+            );
+            assert_eq!(
+                convert(|r, w| base64_to_base85(r, w), base64).unwrap(),
+                encoded
+            );
+        }
+    }
+
+    #[test]
+    fn base64_to_base85_unpadded() {
+        let encoded = convert(|r, w| base64_to_base85(r, w), "cG9sbGVu\nbGVzcw").unwrap();
+        assert_eq!(encoded, "aBpmEWo~R`b8`");
+    }
+
+    #[test]
+    fn transcode_directions() {
+        let framed = crate::Base85Builder::new().framing("<~", "~>").build();
+        for codec in [Codec::default(), framed] {
+            let mut encoded = Vec::new();
+            assert_eq!(
+                transcode(
+                    "pollenless".as_bytes(),
+                    &mut encoded,
+                    Direction::Encode,
+                    &codec
+                )
+                .unwrap(),
+                10
+            );
+            assert_eq!(encoded, codec.encode(b"pollenless").as_bytes());
+            let mut decoded = Vec::new();
+            assert_eq!(
+                transcode(encoded.as_slice(), &mut decoded, Direction::Decode, &codec).unwrap(),
+                10
+            );
+            assert_eq!(decoded, b"pollenless");
+        }
+
+        let checked = crate::Base85Builder::new().checksum(true).build();
+        let err = transcode(
+            b"VPR\xffm".as_slice(),
+            io::sink(),
+            Direction::Decode,
+            &checked,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidByte {
+                index: 3,
+                byte: 0xFF
+            })
+        );
+    }
+
+    #[test]
+    fn transcode_streamed_codecs() {
+        use crate::{Alphabet, Base85Builder, ByteOrder, Padding, Whitespace};
+
+        let codecs = [
+            Base85Builder::new().wrap(5).build(),
+            Base85Builder::new().framing("<~", "~>").build(),
+            Base85Builder::new()
+                .byte_order(ByteOrder::LittleEndian)
+                .build(),
+            Base85Builder::new()
+                .padding(Padding::Zeros)
+                .byte_order(ByteOrder::LittleEndian)
+                .build(),
+            Base85Builder::new()
+                .wrap(4)
+                .max_line_length(4)
+                .whitespace(Whitespace::Reject)
+                .framing("[", "]\n")
+                .build(),
+            Base85Builder::new().alphabet(Alphabet::SHELL_SAFE).build(),
+            #[cfg(feature = "z85")]
+            Codec::Z85,
+        ];
+        for codec in codecs {
+            assert!(codec.stream_encoder().is_some() && codec.stream_decoder().is_some());
+            for len in 0..=10 {
+                let data = &b"pollenless"[..len];
+                let mut encoded = Vec::new();
+                let mut reader = io::BufReader::with_capacity(1, data);
+                transcode(&mut reader, &mut encoded, Direction::Encode, &codec).unwrap();
+                assert_eq!(encoded, codec.encode(data).as_bytes(), "{codec:?}");
+
+                let mut decoded = Vec::new();
+                let mut reader = io::BufReader::with_capacity(1, encoded.as_slice());
+                transcode(&mut reader, &mut decoded, Direction::Decode, &codec).unwrap();
+                let text = String::from_utf8(encoded).unwrap();
+                assert_eq!(decoded, codec.decode(&text).unwrap(), "{codec:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn transcode_streamed_errors() {
+        let codec = crate::Base85Builder::new()
+            .framing("<~", "~>")
+            .wrap(5)
+            .max_line_length(5)
+            .ignore("-")
+            .build();
+        for input in [
+            "<~VPRom\nVE~>",
+            "<~VP-Rom\r\nVE~>",
+            "<~VPRom\nV]~>",
+            "<~VPRom\n |NsC1~>",
+            "<~VPRomV~>",
+            "<~VPRom\r\nVE~",
+            "<VPRom~>",
+            "<~~",
+        ] {
+            let mut decoded = Vec::new();
+            let mut reader = io::BufReader::with_capacity(1, input.as_bytes());
+            let result = transcode(&mut reader, &mut decoded, Direction::Decode, &codec)
+                .map(|_| decoded)
+                .map_err(|err| *err.into_inner().unwrap().downcast::<DecodeError>().unwrap());
+            assert_eq!(result, codec.decode(input), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn transcode_count() {
+        let mut outdata = Vec::new();
+        assert_eq!(
+            base85_to_hex("aBpmEWo~R`b8`".as_bytes(), &mut outdata).unwrap(),
+            10
+        );
+        assert_eq!(hex_to_base85("".as_bytes(), &mut outdata).unwrap(), 0);
+    }
+
+    #[test]
+    fn transcode_invalid() {
+        let conversions: [(Conversion, &str); 6] = [
+            (|r, w| base85_to_hex(r, w), "VE]"),
+            (|r, w| hex_to_base85(r, w), "6g"),
+            (|r, w| hex_to_base85(r, w), "616"),
+            (|r, w| base64_to_base85(r, w), "c=G9"),
+            (|r, w| base64_to_base85(r, w), "cG9s="),
+            (|r, w| base64_to_base85(r, w), "cG9sb"),
+        ];
+        for (function, input) in conversions {
+            let err = convert(function, input).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
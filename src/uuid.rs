@@ -0,0 +1,56 @@
+//! Compact 20 characters identifiers from UUIDs
+
+use ::uuid::Uuid;
+
+use crate::{decode_u128, encode_u128, DecodeError};
+
+/// `encode_uuid()` turns a UUID into a 20 characters identifier
+///
+/// # Example
+///
+/// ```
+/// let id = uuid::Uuid::from_u128(0x1080_0000_0000_0000_0008_0800_200C_417A);
+/// assert_eq!(base85rs::encode_uuid(&id), "4)+k&C#VzJ4br>0wv%Yp");
+/// ```
+#[must_use]
+pub fn encode_uuid(uuid: &Uuid) -> String {
+    encode_u128(uuid.as_u128())
+}
+
+/// `decode_uuid()` try to decode a 20 characters identifier into a UUID
+///
+/// # Errors
+///
+/// Returns the same errors as `decode_u128()`.
+///
+/// # Example
+///
+/// ```
+/// let id = base85rs::decode_uuid("4)+k&C#VzJ4br>0wv%Yp").unwrap();
+/// assert_eq!(id.as_u128(), 0x1080_0000_0000_0000_0008_0800_200C_417A);
+/// ```
+pub fn decode_uuid(instr: &str) -> Result<Uuid, DecodeError> {
+    decode_u128(instr).map(Uuid::from_u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid_roundtrip() {
+        for id in [Uuid::nil(), Uuid::max()] {
+            let encoded = encode_uuid(&id);
+            assert_eq!(encoded.len(), 20);
+            assert_eq!(decode_uuid(&encoded), Ok(id));
+        }
+    }
+
+    #[test]
+    fn decode_uuid_invalid() {
+        assert_eq!(
+            decode_uuid("~~~~~~~~~~~~~~~~~~~~"),
+            Err(DecodeError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Validation of base85 streams, without decoding them into memory
+
+use std::io::{self, Read};
+
+use crate::{incremental, DecodeError};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// What `validate_reader()` accepts, beyond characters of the alphabet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ValidationPolicy {
+    /// Skip spaces, as decoding does, instead of rejecting them
+    pub allow_whitespace: bool,
+    /// Maximum number of decoded bytes, `None` for no limit
+    pub max_decoded_len: Option<u64>,
+}
+
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        Self {
+            allow_whitespace: true,
+            max_decoded_len: None,
+        }
+    }
+}
+
+/// `validate_reader()` checks that `reader` yields well-formed base85 text, and returns its decoded
+/// length
+///
+/// The input is streamed through a small buffer and nothing is written, which makes it suitable as
+/// a pre-flight check of large files.
+///
+/// # Errors
+///
+/// Returns any I/O error from `reader`, and an `io::ErrorKind::InvalidData` error wrapping a
+/// `DecodeError`: `DecodeError::InvalidByte` for a character outside of the alphabet, or a space
+/// not allowed by `policy`, `DecodeError::GroupOverflow` for a group exceeding `u32::MAX`, and
+/// `DecodeError::LimitExceeded` if the decoded length exceeds `policy.max_decoded_len`.
+///
+/// # Example
+///
+/// ```
+/// use base85rs::{validate_reader, ValidationPolicy};
+///
+/// let len = validate_reader("VPRom VE".as_bytes(), ValidationPolicy::default()).unwrap();
+/// assert_eq!(len, 5);
+/// ```
+pub fn validate_reader(mut reader: impl Read, policy: ValidationPolicy) -> io::Result<u64> {
+    let invalid_data = |err| io::Error::new(io::ErrorKind::InvalidData, err);
+
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut decoder = incremental::Decoder::default();
+    let mut offset = 0;
+    let mut total = 0;
+    let mut count = |bytes: &[u8]| {
+        total += bytes.len() as u64;
+        match policy.max_decoded_len {
+            Some(max) if total > max => Err(DecodeError::LimitExceeded),
+            _ => Ok(()),
+        }
+    };
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        let chunk = &buffer[..read];
+        if !policy.allow_whitespace {
+            if let Some(index) = chunk.iter().position(|&byte| byte == 0x20) {
+                return Err(invalid_data(DecodeError::InvalidByte {
+                    index: offset + index,
+                    byte: 0x20,
+                }));
+            }
+        }
+        decoder.update(chunk, &mut count).map_err(invalid_data)?;
+        offset += read;
+    }
+    decoder.finish(&mut count).map_err(invalid_data)?;
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(input: &str, policy: ValidationPolicy) -> Result<u64, DecodeError> {
+        // Read one byte at a time to exercise the offsets
+        let reader = io::BufReader::with_capacity(1, input.as_bytes());
+        validate_reader(reader, policy).map_err(|err| {
+            *err.into_inner()
+                .and_then(|err| err.downcast::<DecodeError>().ok())
+                .unwrap()
+        })
+    }
+
+    #[test]
+    fn validate_word_set() {
+        let policy = ValidationPolicy::default();
+        assert_eq!(validate("", policy), Ok(0));
+        assert_eq!(validate("V{dhCbY *g5Z*6d8bZK ;HZ*B", policy), Ok(18));
+        assert_eq!(
+            validate("VPRo]", policy),
+            Err(DecodeError::InvalidByte {
+                index: 4,
+                byte: b']'
+            })
+        );
+    }
+
+    #[test]
+    fn validate_policy() {
+        let strict = ValidationPolicy {
+            allow_whitespace: false,
+            max_decoded_len: Some(4),
+        };
+        assert_eq!(validate("VPRom", strict), Ok(4));
+        assert_eq!(
+            validate("VPR om", strict),
+            Err(DecodeError::InvalidByte {
+                index: 3,
+                byte: b' '
+            })
+        );
+        assert_eq!(validate("VPRomVE", strict), Err(DecodeError::LimitExceeded));
+    }
+}
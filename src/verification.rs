@@ -0,0 +1,60 @@
+//! Kani proof harnesses, checked with `cargo kani`
+//!
+//! Unlike tests, these harnesses cover every possible input of the given sizes.
+
+use crate::{
+    decode, decode_group, div_rem_85, encode, encode_group, encode_u32_chunk, RFC1924_ALPHABET,
+};
+
+// Every input up to 8 bytes decodes back to itself.
+#[kani::proof]
+#[kani::unwind(10)]
+fn roundtrip() {
+    let data: [u8; 8] = kani::any();
+    let len: usize = kani::any_where(|&len| len <= 8);
+    let data = &data[..len];
+    assert_eq!(decode(&encode(data)).as_deref(), Some(data));
+}
+
+// The chunk math doesn't panic nor overflow, and only produces characters of the alphabet.
+#[kani::proof]
+#[kani::unwind(6)]
+fn encode_chunk_in_alphabet() {
+    let chunk: [u8; 4] = kani::any();
+    let len: usize = kani::any_where(|&len| (1..=4).contains(&len));
+    let mut buffer = [0; 5];
+    assert_eq!(encode_u32_chunk(&chunk[..len], &mut buffer), len);
+    assert!(buffer.iter().all(|chr| RFC1924_ALPHABET.contains(chr)));
+}
+
+// The multiply-shift division of the encoder is exact for every `u32`.
+#[kani::proof]
+fn div_rem_85_exact() {
+    let value: u32 = kani::any();
+    assert_eq!(div_rem_85(value), (value / 85, value % 85));
+}
+
+#[kani::proof]
+#[kani::unwind(6)]
+fn group_roundtrip() {
+    let group: [u8; 4] = kani::any();
+    assert_eq!(decode_group(encode_group(group)), Ok(group));
+}
+
+// Any input up to 10 bytes, groups above `u32::MAX` included, is either decoded or rejected,
+// without panicking nor overflowing.
+#[kani::proof]
+#[kani::unwind(12)]
+fn decode_no_panic() {
+    let instr: [u8; 10] = kani::any();
+    let len: usize = kani::any_where(|&len| len <= 10);
+    let _ = decode(&instr[..len]);
+}
+
+// Any group of 5 bytes is either decoded or rejected, without panicking.
+#[kani::proof]
+#[kani::unwind(6)]
+fn decode_group_no_panic() {
+    let group: [u8; 5] = kani::any();
+    let _ = decode_group(group);
+}
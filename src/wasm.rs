@@ -0,0 +1,40 @@
+//! `wasm-bindgen` bindings, exposing `encode()` and `decode()` to JavaScript
+//!
+//! ```js
+//! import { encode, decode } from "base85rs";
+//!
+//! encode(new Uint8Array([97])); // "VE"
+//! decode("VE"); // Uint8Array [97]
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::{decode_vec, encode};
+
+/// Encode an `Uint8Array` into a base85 `String`
+#[wasm_bindgen(js_name = encode)]
+#[must_use]
+pub fn wasm_encode(data: &[u8]) -> String {
+    encode(data)
+}
+
+/// Decode a base85 `String` into an `Uint8Array`
+///
+/// # Errors
+///
+/// Throws an `Error` describing the `DecodeError` if the input isn't valid.
+#[wasm_bindgen(js_name = decode)]
+pub fn wasm_decode(instr: &str) -> Result<Vec<u8>, JsError> {
+    decode_vec(instr.as_bytes()).map_err(|err| JsError::new(&err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wasm_roundtrip() {
+        assert_eq!(wasm_encode(b"aaaaa"), "VPRomVE");
+        assert_eq!(wasm_decode("VPRomVE").unwrap(), b"aaaaa");
+    }
+}
@@ -0,0 +1,210 @@
+//! Encoding and decoding with the [Z85 alphabet](https://rfc.zeromq.org/spec/32/) of `ZeroMQ`
+//!
+//! Groups are built the same way as in the RFC1924 variant, and so is the handling of trailing
+//! partial groups: inputs don't need to be a multiple of 4 bytes or 5 characters here. Use
+//! `encode_strict()` and `decode_strict()` to enforce the lengths required by the specification.
+//!
+//! # Example
+//!
+//! ```
+//! let encoded = base85rs::z85::encode(&[0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B]);
+//! assert_eq!(encoded, "HelloWorld");
+//! ```
+
+use crate::{decode_with_alphabet, encode_with_alphabet, Alphabet, DecodeError, EncodeError};
+
+/// The 85 characters of the Z85 alphabet, in the order of their values
+pub const ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// `encode()` turns a slice of bytes into a Z85 encoded `String`
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::z85::encode(b"aaaaa"), "vprOMve");
+/// ```
+#[must_use]
+pub fn encode(data: &[u8]) -> String {
+    encode_with_alphabet(data, &Alphabet::Z85)
+}
+
+/// `decode()` try to decode a Z85 encoded &str, skipping whitespaces
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet
+/// and `DecodeError::GroupOverflow` if a group exceeds `u32::MAX`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::z85::decode("vprOMve").unwrap(), b"aaaaa");
+/// ```
+pub fn decode(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_with_alphabet(instr, &Alphabet::Z85)
+}
+
+/// `encode_strict()` turns a slice of bytes into a Z85 encoded `String`, as the specification
+/// requires its length to be a multiple of 4
+///
+/// # Errors
+///
+/// Returns `EncodeError::InvalidLength` if the length of `data` isn't a multiple of 4.
+///
+/// # Example
+///
+/// ```
+/// use base85rs::EncodeError;
+///
+/// assert_eq!(base85rs::z85::encode_strict(b"aaaa").unwrap(), "vprOM");
+/// assert_eq!(base85rs::z85::encode_strict(b"aaaaa"), Err(EncodeError::InvalidLength));
+/// ```
+pub fn encode_strict(data: &[u8]) -> Result<String, EncodeError> {
+    if data.len() % 4 != 0 {
+        return Err(EncodeError::InvalidLength);
+    }
+    Ok(encode(data))
+}
+
+/// `decode_strict()` try to decode a Z85 encoded &str, as the specification requires its length
+/// to be a multiple of 5
+///
+/// Unlike `decode()`, whitespaces aren't accepted.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidLength` if the length of the input isn't a multiple of 5,
+/// `DecodeError::InvalidByte` if it contains a character outside of the alphabet and
+/// `DecodeError::GroupOverflow` if a group exceeds `u32::MAX`.
+///
+/// # Example
+///
+/// ```
+/// use base85rs::DecodeError;
+///
+/// assert_eq!(base85rs::z85::decode_strict("vprOM").unwrap(), b"aaaa");
+/// assert_eq!(base85rs::z85::decode_strict("vprOMve"), Err(DecodeError::InvalidLength));
+/// ```
+pub fn decode_strict(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    if instr.len() % 5 != 0 {
+        return Err(DecodeError::InvalidLength);
+    }
+    if let Some(index) = instr.bytes().position(|byte| byte == 0x20) {
+        return Err(DecodeError::InvalidByte { index, byte: 0x20 });
+    }
+    decode(instr)
+}
+
+/// `encode_key()` turns a 32 bytes `CurveZMQ` key into its 40 characters Z85 form
+///
+/// # Example
+///
+/// ```
+/// let key = base85rs::z85::encode_key(&[0; 32]);
+/// assert_eq!(&key, b"0000000000000000000000000000000000000000");
+/// ```
+#[must_use]
+pub fn encode_key(key: &[u8; 32]) -> [u8; 40] {
+    let mut encoded = [0; 40];
+    for (out, chr) in encoded
+        .iter_mut()
+        .zip(encode_with_alphabet(key, &Alphabet::Z85).bytes())
+    {
+        *out = chr;
+    }
+    encoded
+}
+
+/// `decode_key()` try to decode the 40 characters Z85 form of a `CurveZMQ` key
+///
+/// Unlike `decode()`, whitespaces aren't accepted.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidLength` if the input isn't exactly 40 characters long and
+/// `DecodeError::InvalidByte` if it contains a character outside of the alphabet.
+///
+/// # Example
+///
+/// ```
+/// let key = base85rs::z85::decode_key("0000000000000000000000000000000000000000").unwrap();
+/// assert_eq!(key, [0; 32]);
+/// assert!(base85rs::z85::decode_key("00000").is_err());
+/// ```
+pub fn decode_key(instr: &str) -> Result<[u8; 32], DecodeError> {
+    if instr.len() != 40 {
+        return Err(DecodeError::InvalidLength);
+    }
+    let decoded = decode_strict(instr)?;
+    decoded.try_into().map_err(|_| DecodeError::InvalidLength)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vector from the ZeroMQ Z85 specification
+    const HELLO: [u8; 8] = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B];
+
+    #[test]
+    fn z85_spec_vector() {
+        assert_eq!(encode(&HELLO), "HelloWorld");
+        assert_eq!(decode("HelloWorld").unwrap(), HELLO);
+        assert_eq!(decode("Hello World").unwrap(), HELLO);
+    }
+
+    #[test]
+    fn z85_roundtrip() {
+        for len in 0..16 {
+            let data: Vec<u8> = (0..len).map(|i| i * 17).collect();
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn z85_invalid() {
+        // '~' belongs to RFC1924 but not to Z85
+        assert_eq!(
+            decode("Hello~"),
+            Err(DecodeError::InvalidByte {
+                index: 5,
+                byte: b'~'
+            })
+        );
+    }
+
+    #[test]
+    fn z85_strict() {
+        assert_eq!(encode_strict(&HELLO).unwrap(), "HelloWorld");
+        assert_eq!(encode_strict(&HELLO[..7]), Err(EncodeError::InvalidLength));
+        assert_eq!(encode_strict(&[]).unwrap(), "");
+
+        assert_eq!(decode_strict("HelloWorld").unwrap(), HELLO);
+        assert_eq!(decode_strict("HelloWorl"), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            decode_strict("Hello Worl"),
+            Err(DecodeError::InvalidByte {
+                index: 5,
+                byte: 0x20
+            })
+        );
+    }
+
+    // Curve keys from the CurveZMQ test suite (curve_keygen)
+    #[test]
+    fn z85_curve_key() {
+        let public = "Yne@$w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hID";
+        let key = decode_key(public).unwrap();
+        assert_eq!(&encode_key(&key), public.as_bytes());
+
+        assert_eq!(decode_key(&public[..35]), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            decode_key("Yne@$w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hI "),
+            Err(DecodeError::InvalidByte {
+                index: 39,
+                byte: 0x20
+            })
+        );
+    }
+}
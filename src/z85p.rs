@@ -0,0 +1,119 @@
+//! Encoding and decoding with Z85P, the padded extension of Z85
+//!
+//! Z85 only encodes whole groups of 4 bytes. Z85P completes the last group with zero bytes, and
+//! prepends the number of padding bytes, from 0 to 3, as a Z85 digit: inputs of any length can be
+//! encoded, and are decoded back exactly. The empty input is encoded as the empty string.
+//!
+//! # Example
+//!
+//! ```
+//! let encoded = base85rs::z85p::encode(b"a");
+//! assert_eq!(encoded, "3ve{oc");
+//! assert_eq!(base85rs::z85p::decode(&encoded).unwrap(), b"a");
+//! ```
+
+use crate::{z85, DecodeError};
+
+/// `encode()` turns a slice of bytes into a Z85P encoded `String`
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::z85p::encode(b"aaaaa"), "3vprOMve{oc");
+/// ```
+#[must_use]
+pub fn encode(data: &[u8]) -> String {
+    if data.is_empty() {
+        return String::new();
+    }
+    let padding = (4 - data.len() % 4) % 4;
+    let mut padded = data.to_vec();
+    padded.resize(data.len() + padding, 0);
+
+    let mut encoded = String::with_capacity(1 + padded.len() / 4 * 5);
+    encoded.push(char::from(z85::ALPHABET[padding]));
+    encoded.push_str(&z85::encode(&padded));
+    encoded
+}
+
+/// `decode()` try to decode a Z85P encoded &str
+///
+/// As in strict Z85, whitespaces aren't accepted.
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the first character isn't a padding count from `0` to `3`
+/// or if the input contains a character outside of the alphabet, `DecodeError::GroupOverflow` if a
+/// group exceeds `u32::MAX`, and `DecodeError::InvalidLength` if the encoded groups aren't complete
+/// or are fewer than the padding bytes.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(base85rs::z85p::decode("3vprOMve{oc").unwrap(), b"aaaaa");
+/// assert!(base85rs::z85p::decode("4vprOM").is_err());
+/// ```
+pub fn decode(instr: &str) -> Result<Vec<u8>, DecodeError> {
+    let Some(&first) = instr.as_bytes().first() else {
+        return Ok(Vec::new());
+    };
+    if !(b'0'..=b'3').contains(&first) {
+        return Err(DecodeError::InvalidByte {
+            index: 0,
+            byte: first,
+        });
+    }
+    let padding = usize::from(first - b'0');
+
+    let mut decoded = z85::decode_strict(&instr[1..]).map_err(|err| err.offset_by(1))?;
+    if decoded.is_empty() || decoded.len() < padding {
+        return Err(DecodeError::InvalidLength);
+    }
+    decoded.truncate(decoded.len() - padding);
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO: [u8; 8] = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B];
+
+    #[test]
+    fn z85p_vectors() {
+        assert_eq!(encode(&HELLO), "0HelloWorld");
+        assert_eq!(encode(&HELLO[..7]), "1HelloWork7");
+        assert_eq!(encode(&[]), "");
+        assert_eq!(decode("0HelloWorld").unwrap(), HELLO);
+        assert_eq!(decode("1HelloWork7").unwrap(), HELLO[..7]);
+        assert_eq!(decode("").unwrap(), b"");
+    }
+
+    #[test]
+    fn z85p_roundtrip() {
+        for len in 0..16 {
+            let data: Vec<u8> = (0..len).map(|i| i * 17).collect();
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn z85p_invalid() {
+        assert_eq!(
+            decode("9HelloWorld"),
+            Err(DecodeError::InvalidByte {
+                index: 0,
+                byte: b'9'
+            })
+        );
+        assert_eq!(decode("0"), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            decode("0Hello~orld"),
+            Err(DecodeError::InvalidByte {
+                index: 6,
+                byte: b'~'
+            })
+        );
+        assert_eq!(decode("0HelloWorl"), Err(DecodeError::InvalidLength));
+    }
+}
@@ -0,0 +1,355 @@
+//! Reading and writing `CurveZMQ` certificate files, as managed by `czmq`
+//!
+//! A certificate is made of a public key file and, for our own certificates, a `_secret` file
+//! holding both keys. Both are written in ZPL, the `ZeroMQ` property language, with the keys
+//! encoded in Z85.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{z85, DecodeError, EncodeError};
+
+const PUBLIC_HEADER: &str = "\
+#   ****  Generated by base85rs  ****
+#   ZeroMQ CURVE Public Certificate
+#   Exchange securely, or use a secure mechanism to verify the contents
+#   of this file after exchange. Store public certificates in your home
+#   directory, in the .curve subdirectory.
+";
+
+const SECRET_HEADER: &str = "\
+#   ****  Generated by base85rs  ****
+#   ZeroMQ CURVE **Secret** Certificate
+#   DO NOT PROVIDE THIS FILE TO OTHER USERS nor change its permissions.
+";
+
+/// A `CurveZMQ` certificate: a key pair, the secret key being unknown for peers' certificates,
+/// and metadata
+///
+/// The secret key is redacted from the `Debug` output. Certificates aren't `PartialEq`, as a
+/// derived comparison wouldn't be constant-time on the secret key: they are told apart by their
+/// `public_key`.
+#[derive(Clone)]
+pub struct Certificate {
+    /// Public key
+    pub public_key: [u8; 32],
+    /// Secret key, only present in `_secret` files
+    pub secret_key: Option<[u8; 32]>,
+    /// Metadata, as `(name, value)` pairs in their order of appearance
+    pub metadata: Vec<(String, String)>,
+}
+
+impl fmt::Debug for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Certificate")
+            .field("public_key", &self.public_key)
+            .field("secret_key", &self.secret_key.map(|_| "<redacted>"))
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
+
+impl Certificate {
+    /// `parse()` reads a certificate from the content of a public or secret certificate file
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::InvalidCertificate` if the content isn't a valid ZPL certificate,
+    /// and any error from `z85::decode_key()` for a malformed key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let cert = base85rs::zcert::Certificate::parse(
+    ///     "curve\n    public-key = \"0000000000000000000000000000000000000000\"\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(cert.public_key, [0; 32]);
+    /// ```
+    pub fn parse(instr: &str) -> Result<Self, DecodeError> {
+        let mut public_key = None;
+        let mut secret_key = None;
+        let mut metadata = Vec::new();
+        let mut section = "";
+
+        for line in instr.lines() {
+            let content = line.trim_end();
+            if content.trim_start().is_empty() || content.trim_start().starts_with('#') {
+                continue;
+            }
+            if !content.starts_with(' ') {
+                section = content;
+                continue;
+            }
+            let (name, value) = content
+                .split_once('=')
+                .ok_or(DecodeError::InvalidCertificate)?;
+            let (name, value) = (name.trim(), unquote(value.trim()));
+            match (section, name) {
+                ("metadata", _) => metadata.push((name.to_string(), value.to_string())),
+                ("curve", "public-key") => public_key = Some(z85::decode_key(value)?),
+                ("curve", "secret-key") => secret_key = Some(z85::decode_key(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            public_key: public_key.ok_or(DecodeError::InvalidCertificate)?,
+            secret_key,
+            metadata,
+        })
+    }
+
+    /// `to_public_string()` returns the content of the public certificate file
+    ///
+    /// Metadata values are written between double quotes, or single quotes if they contain a
+    /// double quote, as ZPL has no escapes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::InvalidMetadata` for a name that isn't made of letters, digits and
+    /// `$-_@.&+/`, and for a value holding a control character or both kinds of quotes.
+    pub fn to_public_string(&self) -> Result<String, EncodeError> {
+        self.to_zpl(PUBLIC_HEADER, false)
+    }
+
+    /// `to_secret_string()` returns the content of the secret certificate file, if the secret key
+    /// is known
+    ///
+    /// # Errors
+    ///
+    /// Same as `to_public_string()`.
+    pub fn to_secret_string(&self) -> Result<Option<String>, EncodeError> {
+        if self.secret_key.is_some() {
+            self.to_zpl(SECRET_HEADER, true).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn to_zpl(&self, header: &str, secret: bool) -> Result<String, EncodeError> {
+        let mut lines = vec![header.to_string(), "metadata".to_string()];
+        for (name, value) in &self.metadata {
+            let valid_name = !name.is_empty()
+                && name
+                    .bytes()
+                    .all(|chr| chr.is_ascii_alphanumeric() || b"$-_@.&+/".contains(&chr));
+            let quote = if value.contains('"') { '\'' } else { '"' };
+            if !valid_name || value.contains(quote) || value.chars().any(char::is_control) {
+                return Err(EncodeError::InvalidMetadata);
+            }
+            lines.push(format!("    {name} = {quote}{value}{quote}"));
+        }
+        lines.push("curve".to_string());
+        let key = z85::encode_key(&self.public_key);
+        lines.push(format!("    public-key = \"{}\"", key_str(&key)));
+        if let (true, Some(secret_key)) = (secret, &self.secret_key) {
+            let key = z85::encode_key(secret_key);
+            lines.push(format!("    secret-key = \"{}\"", key_str(&key)));
+        }
+        lines.push(String::new());
+        Ok(lines.join("\n"))
+    }
+
+    /// `load()` reads a certificate from `path`, or from `path` followed by `_secret` when this
+    /// file exists, as `czmq` does
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error, and an `io::ErrorKind::InvalidData` error wrapping the
+    /// `DecodeError` for an invalid certificate.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(secret_path(path)).or_else(|err| {
+            if err.kind() == io::ErrorKind::NotFound {
+                fs::read_to_string(path)
+            } else {
+                Err(err)
+            }
+        })?;
+        Self::parse(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// `save()` writes the public certificate to `path`, and the secret certificate, if any, to
+    /// `path` followed by `_secret`
+    ///
+    /// On unix, the secret certificate is made readable and writable by its owner only, before
+    /// the secret key is written to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error, and an `io::ErrorKind::InvalidInput` error wrapping the
+    /// `EncodeError` of metadata that can't be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let invalid_input = |err| io::Error::new(io::ErrorKind::InvalidInput, err);
+        let public = self.to_public_string().map_err(invalid_input)?;
+        let secret = self.to_secret_string().map_err(invalid_input)?;
+        fs::write(path, public)?;
+        if let Some(secret) = secret {
+            let mut options = OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut file = options.open(secret_path(path))?;
+            // The mode only applies to new files
+            #[cfg(unix)]
+            file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+            file.write_all(secret.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+// ZPL values may be quoted with single or double quotes.
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&quote| value.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(value)
+}
+
+// Z85 characters are ASCII only
+fn key_str(key: &[u8; 40]) -> &str {
+    std::str::from_utf8(key).unwrap_or_default()
+}
+
+fn secret_path(path: &Path) -> PathBuf {
+    let mut secret = path.as_os_str().to_owned();
+    secret.push("_secret");
+    PathBuf::from(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Secret certificate written by czmq's zcert_save()
+    const CZMQ_SECRET: &str = "\
+#   ****  Generated on 2013-04-16 16:43:54 by CZMQ  ****
+#   ZeroMQ CURVE **Secret** Certificate
+#   DO NOT PROVIDE THIS FILE TO OTHER USERS nor change its permissions.
+
+metadata
+    name = \"Pieter Hintjens\"
+    email = \"ph@imatix.com\"
+curve
+    public-key = \"Yne@$w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hID\"
+    secret-key = \"D:)Q[IlAW!ahhC2ac:9*A}h:p?([4%wOTJ%JR%cs\"
+";
+
+    #[test]
+    fn parse_czmq_certificate() {
+        let cert = Certificate::parse(CZMQ_SECRET).unwrap();
+        assert_eq!(
+            cert.public_key,
+            z85::decode_key("Yne@$w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hID").unwrap()
+        );
+        assert_eq!(
+            cert.secret_key,
+            Some(z85::decode_key("D:)Q[IlAW!ahhC2ac:9*A}h:p?([4%wOTJ%JR%cs").unwrap())
+        );
+        assert_eq!(
+            cert.metadata,
+            [
+                ("name".to_string(), "Pieter Hintjens".to_string()),
+                ("email".to_string(), "ph@imatix.com".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn certificate_roundtrip() {
+        let cert = Certificate::parse(CZMQ_SECRET).unwrap();
+        let public = Certificate::parse(&cert.to_public_string().unwrap()).unwrap();
+        assert_eq!(public.public_key, cert.public_key);
+        assert_eq!(public.secret_key, None);
+        assert_eq!(public.metadata, cert.metadata);
+        assert_eq!(public.to_secret_string(), Ok(None));
+
+        let secret = Certificate::parse(&cert.to_secret_string().unwrap().unwrap()).unwrap();
+        assert_eq!(secret.public_key, cert.public_key);
+        assert_eq!(secret.secret_key, cert.secret_key);
+        assert_eq!(secret.metadata, cert.metadata);
+    }
+
+    #[test]
+    fn certificate_debug_redacted() {
+        let cert = Certificate::parse(CZMQ_SECRET).unwrap();
+        let debug = format!("{cert:?}");
+        assert!(debug.contains("secret_key: Some(\"<redacted>\")"));
+        let secret_key = cert.secret_key.unwrap();
+        assert!(!debug.contains(&format!("{secret_key:?}")));
+        assert!(debug.contains(&format!("{:?}", cert.public_key)));
+    }
+
+    #[test]
+    fn metadata_quotes() {
+        let mut cert = Certificate::parse(CZMQ_SECRET).unwrap();
+        cert.metadata = vec![
+            ("nick".to_string(), "say \"hi\"".to_string()),
+            ("motto".to_string(), "don't".to_string()),
+        ];
+        let public = cert.to_public_string().unwrap();
+        assert!(public.contains("    nick = 'say \"hi\"'\n"));
+        assert_eq!(Certificate::parse(&public).unwrap().metadata, cert.metadata);
+
+        for (name, value) in [
+            ("nick", "\"don't\""),
+            ("nick", "a\nb"),
+            ("a name", "x"),
+            ("", "x"),
+        ] {
+            cert.metadata = vec![(name.to_string(), value.to_string())];
+            assert_eq!(cert.to_public_string(), Err(EncodeError::InvalidMetadata));
+            assert_eq!(cert.to_secret_string(), Err(EncodeError::InvalidMetadata));
+        }
+        let path =
+            std::env::temp_dir().join(format!("base85rs-{}-invalid.key", std::process::id()));
+        let err = cert.save(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn certificate_files() {
+        let path = std::env::temp_dir().join(format!("base85rs-{}.key", std::process::id()));
+        let cert = Certificate::parse(CZMQ_SECRET).unwrap();
+        // An existing secret file loses its other permissions
+        fs::write(secret_path(&path), "").unwrap();
+        cert.save(&path).unwrap();
+        let loaded = Certificate::load(&path).unwrap();
+        assert_eq!(loaded.public_key, cert.public_key);
+        assert_eq!(loaded.secret_key, cert.secret_key);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(secret_path(&path))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_file(secret_path(&path)).unwrap();
+        assert_eq!(Certificate::load(&path).unwrap().secret_key, None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(
+            Certificate::parse("metadata\n").err(),
+            Some(DecodeError::InvalidCertificate)
+        );
+        assert_eq!(
+            Certificate::parse("curve\n    public-key\n").err(),
+            Some(DecodeError::InvalidCertificate)
+        );
+        assert_eq!(
+            Certificate::parse("curve\n    public-key = \"0000\"\n").err(),
+            Some(DecodeError::InvalidLength)
+        );
+    }
+}
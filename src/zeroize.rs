@@ -0,0 +1,67 @@
+//! Encoding and decoding of secrets, with every buffer wiped from memory once dropped
+//!
+//! Outputs are allocated at their final size up front, so that no reallocation leaves a copy
+//! of the data behind, and the internal buffers used along the way are zeroized as well.
+
+use ::zeroize::Zeroizing;
+
+use crate::{ascii_string, decode_with_sink, decoded_len, encode_slice, encoded_len, DecodeError};
+
+/// `encode_zeroizing()` turns a slice of bytes into a base85 encoded `String` zeroized on drop
+///
+/// # Example
+///
+/// ```
+/// let encoded = base85rs::encode_zeroizing(b"aaaaa");
+/// assert_eq!(encoded.as_str(), "VPRomVE");
+/// ```
+#[must_use]
+pub fn encode_zeroizing(data: &[u8]) -> Zeroizing<String> {
+    let mut outdata = Zeroizing::new(vec![0; encoded_len(data.len())]);
+    // Can't fail, the output has the exact encoded size
+    let _ = encode_slice(data, &mut outdata);
+    // The allocation is reused as is
+    Zeroizing::new(ascii_string(std::mem::take(&mut *outdata)))
+}
+
+/// `decode_zeroizing()` try to decode a base85 encoded &str into a `Vec<u8>` zeroized on drop
+///
+/// # Errors
+///
+/// Returns `DecodeError::InvalidByte` if the input contains a character outside of the alphabet,
+/// the partially decoded data is zeroized before returning.
+///
+/// # Example
+///
+/// ```
+/// let decoded = base85rs::decode_zeroizing("VPRomVE").unwrap();
+/// assert_eq!(decoded.as_slice(), b"aaaaa");
+/// ```
+pub fn decode_zeroizing(instr: &str) -> Result<Zeroizing<Vec<u8>>, DecodeError> {
+    let mut outdata = Zeroizing::new(Vec::with_capacity(decoded_len(instr.len())));
+    decode_with_sink(instr.as_bytes(), |bytes| {
+        outdata.extend(bytes);
+        Ok(())
+    })?;
+    Ok(outdata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zeroizing_roundtrip() {
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = encode_zeroizing(&data);
+        assert_eq!(encoded.as_str(), crate::encode(&data));
+        assert_eq!(*decode_zeroizing(&encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn decode_zeroizing_no_reallocation() {
+        let decoded = decode_zeroizing("a%F63ZE1 92bZKvH").unwrap();
+        assert_eq!(decoded.as_slice(), b"relimitation");
+        assert_eq!(decoded.capacity(), decoded_len(16));
+    }
+}